@@ -0,0 +1,299 @@
+//! Area objects and signposts
+//!
+//! An object or item with the `\l` symbol draws a shape on the map. Its comment starts with
+//! `Tyy/Cxx`: the shape digit `T`, the latitude offset `yy`, the colour `C` and the longitude
+//! offset `xx`. Each offset is the square root of the extent of the shape from the object's
+//! position in hundredths of a degree, so `10` is one degree. Lines may be followed by the width
+//! of their corridor in miles as `{w}`. Colours 10 to 15 replace the `/` with `1`, so `Tyy1Cxx`
+//! is colour `1C` and the field is always seven bytes.
+//!
+//! | Digit | Shape    | Digit | Shape           |
+//! |-------|----------|-------|-----------------|
+//! | 0     | circle   | 5     | filled circle   |
+//! | 1     | line     | 6     | line, leftwards |
+//! | 2     | ellipse  | 7     | filled ellipse  |
+//! | 3     | triangle | 8     | filled triangle |
+//! | 4     | box      | 9     | filled box      |
+//!
+//! Colours 0 to 7 are black, blue, green, cyan, red, violet, yellow and grey at high intensity,
+//! and 8 to 15 the same at low intensity.
+//!
+//! An object with the `\m` symbol is a signpost, showing up to three characters given in the
+//! comment as `{xxx}`, such as a speed limit or a mile marker.
+//!
+//! Decoding moves these into the `area` and `signpost` fields of `PositionFrame`, and encoding
+//! writes them back at the start of the comment.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::area::{Colour, Shape};
+//! # use aprs::object;
+//! # fn main() {
+//! let object = object::parse_object(b";SEARCH 1 *092345z4903.50N\\07201.75Wl9121225Sector 1").unwrap();
+//! let area = object.position.area.unwrap();
+//! assert_eq!(area.shape, Shape::Box);
+//! assert!(area.filled);
+//! assert_eq!(area.colour, Colour::Red);
+//! assert!(!area.bright);
+//! assert_eq!(area.lat_offset, 1.44);
+//! assert_eq!(area.lng_offset, 6.25);
+//! assert_eq!(object.position.comment, b"Sector 1".to_vec());
+//! # }
+//! ```
+
+use structs::PositionFrame;
+use errors::EncodeError;
+use util::check_printable;
+
+
+/// Longest signpost text.
+pub const MAX_SIGNPOST_LEN: usize = 3;
+
+const MAX_OFFSET_ROOT: u8 = 99;
+const MAX_WIDTH: u16 = 999;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Circle,
+    /// Line running down and to the right from the object's position
+    LineRight,
+    /// Line running down and to the left from the object's position
+    LineLeft,
+    Ellipse,
+    Triangle,
+    Box,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colour {
+    Black,
+    Blue,
+    Green,
+    Cyan,
+    Red,
+    Violet,
+    Yellow,
+    Grey,
+}
+
+const COLOURS: [Colour; 8] = [
+    Colour::Black,
+    Colour::Blue,
+    Colour::Green,
+    Colour::Cyan,
+    Colour::Red,
+    Colour::Violet,
+    Colour::Yellow,
+    Colour::Grey,
+];
+
+/// Shape drawn by an area object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub shape: Shape,
+    /// Whether the shape is filled with its colour; lines cannot be
+    pub filled: bool,
+    pub colour: Colour,
+    /// High intensity colour, rather than low
+    pub bright: bool,
+    /// Extent of the shape from the object's position in degrees of latitude
+    pub lat_offset: f32,
+    /// Extent of the shape from the object's position in degrees of longitude
+    pub lng_offset: f32,
+    /// Width of a line's corridor in miles
+    pub width: Option<u16>,
+}
+
+impl Area {
+    pub fn new() -> Area {
+        Area {
+            shape: Shape::Box,
+            filled: false,
+            colour: Colour::Red,
+            bright: true,
+            lat_offset: 0.0,
+            lng_offset: 0.0,
+            width: None,
+        }
+    }
+}
+
+impl Default for Area {
+    fn default() -> Area {
+        Area::new()
+    }
+}
+
+impl Area {
+    /// Decode a `Tyy/Cxx` or `Tyy1Cxx` area at the start of a comment, with the corridor width of a line.
+    ///
+    /// Returns the area and the number of bytes it took up.
+    pub fn decode(comment: &[u8]) -> Option<(Area, usize)> {
+        let digit = |ind: usize| {
+            comment.get(ind).filter(|byt| byt.is_ascii_digit()).map(|byt| byt - b'0')
+        };
+        let (shape, filled): (Shape, bool) = match digit(0)? {
+            0 => (Shape::Circle, false),
+            1 => (Shape::LineRight, false),
+            2 => (Shape::Ellipse, false),
+            3 => (Shape::Triangle, false),
+            4 => (Shape::Box, false),
+            5 => (Shape::Circle, true),
+            6 => (Shape::LineLeft, false),
+            7 => (Shape::Ellipse, true),
+            8 => (Shape::Triangle, true),
+            _ => (Shape::Box, true),
+        };
+        let lat_root: u8 = digit(1)? * 10 + digit(2)?;
+        // Colours 10 to 15 replace the `/` with a `1`
+        let colour: u8 = match comment.get(3) {
+            Some(&b'/') => digit(4)?,
+            Some(&b'1') if digit(4)? <= 5 => 10 + digit(4)?,
+            _ => return None,
+        };
+        let lng_root: u8 = digit(5)? * 10 + digit(6)?;
+        let mut len: usize = 7;
+
+        let mut width: Option<u16> = None;
+        if shape == Shape::LineRight || shape == Shape::LineLeft {
+            if let Some((text, end)) = decode_braces(&comment[len..]) {
+                if text.iter().all(|byt| byt.is_ascii_digit()) {
+                    width = String::from_utf8_lossy(text).parse().ok();
+                    len += end;
+                }
+            }
+        }
+
+        let area: Area = Area {
+            shape,
+            filled,
+            colour: COLOURS[usize::from(colour % 8)],
+            bright: colour < 8,
+            lat_offset: offset(lat_root),
+            lng_offset: offset(lng_root),
+            width,
+        };
+        Some((area, len))
+    }
+
+    /// Encode as `Tyy/Cxx`, or `Tyy1Cxx` for colours 10 to 15, rounding the offsets to the nearest code.
+    ///
+    /// Lines are never filled, offsets are limited to 98.01 degrees and the corridor width,
+    /// given only for lines, to 999 miles.
+    pub fn encode(&self) -> Vec<u8> {
+        let shape: u8 = match (self.shape, self.filled) {
+            (Shape::Circle, false) => 0,
+            (Shape::LineRight, _) => 1,
+            (Shape::Ellipse, false) => 2,
+            (Shape::Triangle, false) => 3,
+            (Shape::Box, false) => 4,
+            (Shape::Circle, true) => 5,
+            (Shape::LineLeft, _) => 6,
+            (Shape::Ellipse, true) => 7,
+            (Shape::Triangle, true) => 8,
+            (Shape::Box, true) => 9,
+        };
+        let colour: u8 = self.colour as u8 + if self.bright { 0 } else { 8 };
+        let lat_root: u8 = offset_root(self.lat_offset).min(MAX_OFFSET_ROOT);
+        let lng_root: u8 = offset_root(self.lng_offset).min(MAX_OFFSET_ROOT);
+        let separator: char = if colour >= 10 { '1' } else { '/' };
+        let mut encoded: Vec<u8> =
+            format!("{}{:02}{}{}{:02}", shape, lat_root, separator, colour % 10, lng_root).into_bytes();
+        if let (Some(width), true) = (self.width, self.is_line()) {
+            encoded.extend(format!("{{{}}}", width.min(MAX_WIDTH)).as_bytes());
+        }
+        encoded
+    }
+
+    fn is_line(&self) -> bool {
+        self.shape == Shape::LineRight || self.shape == Shape::LineLeft
+    }
+}
+
+/// Move the area of a position report with the `\l` symbol to its `area` field.
+pub(crate) fn extract_area(position: &mut PositionFrame) {
+    if position.table != b"\\" || position.symbol != b'l' {
+        return;
+    }
+    if let Some((area, len)) = Area::decode(&position.comment) {
+        position.area = Some(area);
+        position.comment.drain(..len);
+    }
+}
+
+/// Move the text of a position report with the `\m` symbol to its `signpost` field.
+pub(crate) fn extract_signpost(position: &mut PositionFrame) {
+    if position.table != b"\\" || position.symbol != b'm' {
+        return;
+    }
+    let signpost: Option<(String, usize)> = decode_braces(&position.comment)
+        .filter(|&(text, _)| !text.is_empty() && text.len() <= MAX_SIGNPOST_LEN)
+        .map(|(text, end)| (String::from_utf8_lossy(text).into_owned(), end));
+    if let Some((text, end)) = signpost {
+        position.signpost = Some(text);
+        position.comment.drain(..end);
+    }
+}
+
+/// Encode signpost text as `{xxx}`, keeping the first three characters.
+pub(crate) fn create_signpost(text: &str) -> Vec<u8> {
+    let text: String = text.chars()
+        .filter(|chr| *chr != '{' && *chr != '}')
+        .take(MAX_SIGNPOST_LEN)
+        .collect();
+    format!("{{{}}}", text).into_bytes()
+}
+
+/// Check that the area or signpost of an object or item can be encoded without change.
+pub(crate) fn check(position: &PositionFrame) -> Result<(), EncodeError> {
+    if let Some(ref area) = position.area {
+        if position.table != b"\\" || position.symbol != b'l' {
+            return Err(EncodeError::Conflict { field: "area", other: "symbol" });
+        }
+        if area.filled && area.is_line() {
+            return Err(EncodeError::Conflict { field: "filled", other: "shape" });
+        }
+        if area.width.is_some() && !area.is_line() {
+            return Err(EncodeError::Conflict { field: "width", other: "shape" });
+        }
+        if area.width.is_some_and(|width| width > MAX_WIDTH) {
+            return Err(EncodeError::OutOfRange { field: "width" });
+        }
+        if offset_root(area.lat_offset) > MAX_OFFSET_ROOT {
+            return Err(EncodeError::OutOfRange { field: "lat offset" });
+        }
+        if offset_root(area.lng_offset) > MAX_OFFSET_ROOT {
+            return Err(EncodeError::OutOfRange { field: "lng offset" });
+        }
+    }
+    if let Some(ref text) = position.signpost {
+        if position.table != b"\\" || position.symbol != b'm' {
+            return Err(EncodeError::Conflict { field: "signpost", other: "symbol" });
+        }
+        if text.is_empty() {
+            return Err(EncodeError::TooShort { field: "signpost", min: 1 });
+        }
+        if text.len() > MAX_SIGNPOST_LEN {
+            return Err(EncodeError::TooLong { field: "signpost", max: MAX_SIGNPOST_LEN });
+        }
+        check_printable(text.as_bytes(), b"{}", "signpost")?;
+    }
+    Ok(())
+}
+
+// Text between a `{` at the start and the next `}`, with the length up to the `}`.
+fn decode_braces(data: &[u8]) -> Option<(&[u8], usize)> {
+    if data.first() != Some(&b'{') {
+        return None;
+    }
+    let end: usize = data.iter().position(|byt| *byt == b'}')?;
+    Some((&data[1..end], end + 1))
+}
+
+fn offset(root: u8) -> f32 {
+    f32::from(root) * f32::from(root) / 100.0
+}
+
+fn offset_root(offset: f32) -> u8 {
+    (offset.abs() * 100.0).sqrt().round().min(255.0) as u8
+}
@@ -0,0 +1,67 @@
+//! Base-91 encoding used by compressed APRS data.
+//!
+//! Each digit is a printable ASCII character from `!` (0) to `{` (90). Values are written most
+//! significant digit first. Compressed positions, compressed altitude and compressed telemetry
+//! all use this codec.
+
+/// Value of the first base-91 digit, `!`.
+pub const OFFSET: u8 = 33;
+/// Number base.
+pub const BASE: u32 = 91;
+
+
+/// Encode a value into exactly `width` base-91 digits.
+///
+/// Returns `None` if the value does not fit in `width` digits.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::base91::encode;
+/// # fn main() {
+/// assert_eq!(encode(0, 2), Some(b"!!".to_vec()));
+/// assert_eq!(encode(8280, 2), Some(b"{{".to_vec()));
+/// assert_eq!(encode(8281, 2), None);
+/// # }
+/// ```
+pub fn encode(value: u32, width: usize) -> Option<Vec<u8>> {
+    let mut remaining: u64 = u64::from(value);
+    let mut digits: Vec<u8> = vec![OFFSET; width];
+
+    for digit in digits.iter_mut().rev() {
+        *digit = (remaining % u64::from(BASE)) as u8 + OFFSET;
+        remaining /= u64::from(BASE);
+    }
+    if remaining != 0 {
+        return None;
+    }
+    Some(digits)
+}
+
+/// Decode a string of base-91 digits.
+///
+/// Returns `None` if any byte is outside the base-91 alphabet or the value overflows a `u32`.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::base91::decode;
+/// # fn main() {
+/// assert_eq!(decode(b"5L!!"), Some(15427503));
+/// assert_eq!(decode(b"{{"), Some(8280));
+/// assert_eq!(decode(b"a|"), None);
+/// # }
+/// ```
+pub fn decode(digits: &[u8]) -> Option<u32> {
+    let mut value: u32 = 0;
+    for byt in digits {
+        if !is_digit(*byt) {
+            return None;
+        }
+        value = value.checked_mul(BASE)?.checked_add(u32::from(byt - OFFSET))?;
+    }
+    Some(value)
+}
+
+/// Check whether a byte is a base-91 digit.
+pub fn is_digit(byt: u8) -> bool {
+    byt >= OFFSET && byt < OFFSET + BASE as u8
+}
@@ -0,0 +1,182 @@
+//! Station capabilities
+//!
+//! A `<` information field lists what a station can do as comma separated tokens, each a bare
+//! keyword or a `KEY=value` pair. Igates send them in answer to `?IGATE?` queries, giving the
+//! number of messages they have gated to RF and the number of local stations they hear:
+//! `<IGATE,MSG_CNT=43,LOC_CNT=14`.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::capabilities::{Capabilities, parse_capabilities};
+//! # fn main() {
+//! let capabilities = parse_capabilities(b"<IGATE,MSG_CNT=43,LOC_CNT=14").unwrap();
+//! assert!(capabilities.is_igate());
+//! assert_eq!(capabilities.message_count(), Some(43));
+//! assert_eq!(capabilities.local_count(), Some(14));
+//!
+//! let info = Capabilities::igate(43, 14).create_info_field().unwrap();
+//! assert_eq!(info, b"<IGATE,MSG_CNT=43,LOC_CNT=14".to_vec());
+//! # }
+//! ```
+
+use std::str;
+
+use structs::{Frame, InformationField};
+use errors::{ParseError, EncodeError};
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub key: String,
+    /// Value after the `=`, absent for bare keywords such as `IGATE`
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    /// Tokens in the order they were sent
+    pub tokens: Vec<Token>,
+}
+
+impl Capabilities {
+    pub fn new() -> Capabilities {
+        Capabilities {
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Capabilities of an igate that has gated `message_count` messages and hears
+    /// `local_count` local stations.
+    pub fn igate(message_count: u32, local_count: u32) -> Capabilities {
+        let mut capabilities: Capabilities = Capabilities::new();
+        capabilities.set("IGATE", None);
+        capabilities.set("MSG_CNT", Some(&message_count.to_string()));
+        capabilities.set("LOC_CNT", Some(&local_count.to_string()));
+        capabilities
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Capabilities {
+        Capabilities::new()
+    }
+}
+
+impl Capabilities {
+    /// Decode the capabilities carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Capabilities, ParseError> {
+        Capabilities::from_info_field(&frame.info)
+    }
+
+    /// Decode a `<` information field into capabilities.
+    pub fn from_info_field(info: &InformationField) -> Result<Capabilities, ParseError> {
+        parse_capabilities(&info.data)
+    }
+
+    /// Whether a token with the key is present, with or without a value.
+    pub fn has(&self, key: &str) -> bool {
+        self.tokens.iter().any(|token| token.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Value of the first token with the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tokens.iter()
+            .find(|token| token.key.eq_ignore_ascii_case(key))
+            .and_then(|token| token.value.as_deref())
+    }
+
+    /// Set the value of a token, replacing an existing token with the key or adding one.
+    pub fn set(&mut self, key: &str, value: Option<&str>) {
+        let value: Option<String> = value.map(|value| value.to_string());
+        match self.tokens.iter_mut().find(|token| token.key.eq_ignore_ascii_case(key)) {
+            Some(token) => token.value = value,
+            None => self.tokens.push(Token { key: key.to_string(), value }),
+        }
+    }
+
+    /// Whether the station announces itself as an igate.
+    pub fn is_igate(&self) -> bool {
+        self.has("IGATE")
+    }
+
+    /// `MSG_CNT`: number of messages the igate has gated to RF.
+    pub fn message_count(&self) -> Option<u32> {
+        self.get("MSG_CNT").and_then(|value| value.parse().ok())
+    }
+
+    /// `LOC_CNT`: number of local stations the igate hears.
+    pub fn local_count(&self) -> Option<u32> {
+        self.get("LOC_CNT").and_then(|value| value.parse().ok())
+    }
+
+    /// Encode as a `<` information field.
+    ///
+    /// There must be at least one token. Keys may not be empty or contain `,` or `=`, and values
+    /// may not contain `,`; both are limited to printable ASCII.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        if self.tokens.is_empty() {
+            return Err(EncodeError::Missing { field: "tokens" });
+        }
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b'<');
+        for (ind, token) in self.tokens.iter().enumerate() {
+            if token.key.is_empty() {
+                return Err(EncodeError::TooShort { field: "key", min: 1 });
+            }
+            if let Some(offset) = token.key.bytes().position(|byt| !is_token_byte(byt) || byt == b'=') {
+                return Err(EncodeError::InvalidCharacter { field: "key", offset });
+            }
+            if ind > 0 {
+                info.push(b',');
+            }
+            info.extend_from_slice(token.key.as_bytes());
+            if let Some(ref value) = token.value {
+                if let Some(offset) = value.bytes().position(|byt| !is_token_byte(byt)) {
+                    return Err(EncodeError::InvalidCharacter { field: "value", offset });
+                }
+                info.push(b'=');
+                info.extend_from_slice(value.as_bytes());
+            }
+        }
+        Ok(info)
+    }
+}
+
+fn is_token_byte(byt: u8) -> bool {
+    byt.is_ascii_graphic() && byt != b','
+}
+
+/// Parse a `<` station capabilities information field.
+///
+/// Empty tokens, as left by a trailing comma, are skipped.
+pub fn parse_capabilities(raw_data: &[u8]) -> Result<Capabilities, ParseError> {
+    match raw_data.first() {
+        Some(&b'<') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    let data: &str = str::from_utf8(raw_data)
+        .map_err(|e| ParseError::InfoField { offset: e.valid_up_to() })?;
+    let data: &str = data.trim_end_matches(&['\r', '\n', ' '][..]);
+
+    let mut capabilities: Capabilities = Capabilities::new();
+    let mut offset: usize = 1;
+    for field in data[1..].split(',') {
+        let token: &str = field.trim();
+        if !token.is_empty() {
+            let (key, value): (&str, Option<&str>) = match token.find('=') {
+                Some(pos) => (token[..pos].trim_end(), Some(token[(pos + 1)..].trim_start())),
+                None => (token, None),
+            };
+            if key.is_empty() {
+                return Err(ParseError::InfoField { offset: offset + field.find('=').unwrap_or(0) });
+            }
+            capabilities.tokens.push(Token {
+                key: key.to_string(),
+                value: value.map(|value| value.to_string()),
+            });
+        }
+        offset += field.len() + 1;
+    }
+    Ok(capabilities)
+}
@@ -0,0 +1,69 @@
+//! Time sources
+//!
+//! Stateful components such as the messenger read the time through the `Clock` trait, so they can
+//! run against the system clock in production and a manually advanced clock in tests.
+
+use std::cell::Cell;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+pub trait Clock {
+    /// Time elapsed since the Unix epoch.
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// Clock backed by the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/// Clock that only moves when told to.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use std::time::Duration;
+/// # use aprs::clock::{Clock, ManualClock};
+/// # fn main() {
+/// let clock = ManualClock::new(Duration::from_secs(100));
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now(), Duration::from_secs(130));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new(now: Duration) -> ManualClock {
+        ManualClock {
+            now: Cell::new(now)
+        }
+    }
+}
+
+impl ManualClock {
+    pub fn set(&self, now: Duration) {
+        self.now.set(now);
+    }
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
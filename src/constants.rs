@@ -1,6 +1,6 @@
 //! Constants for use throughout the library.
 //!
-//! Constants include APRS-IS servers, HTTP headers, AX.25 control bytes, symbol mapping, and KISS
+//! Constants include APRS-IS servers, HTTP headers, AX.25 control bytes and KISS
 //! data.
 
 use std::collections::HashMap;
@@ -46,20 +46,6 @@ pub const AX25_PROTOCOL_ID: u8 = 0xF0;
 /// A good place to split AX.25 address from information fields.
 pub const ADDR_INFO_DELIM: &'static [u8] = &[0x03, 0xF0];
 
-/// Data symbol map
-lazy_static! {
-    pub static ref DATA_TYPE_MAP: HashMap<u8, &'static str> = {
-        let mut d: HashMap<u8, &'static str> = HashMap::new();
-        d.insert(b'>', "status");
-        d.insert(b'!', "position_nots_nomsg");
-        d.insert(b'=', "position_nots_msg");
-        d.insert(b'T', "telemetry");
-        d.insert(b';', "object");
-        d.insert(b'`', "old_mice");
-        d
-    };
-}
-
 /// KISS Command Codes
 /// https://en.wikipedia.org/wiki/KISS_(TNC)#Command_Codes
 pub const KISS_DATA_FRAME: u8 = 0x00;
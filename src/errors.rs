@@ -0,0 +1,134 @@
+//! Parse and encode errors
+//!
+//! Parsers return a `ParseError` instead of panicking on malformed input. Each variant names the
+//! stage of the frame that failed and carries the byte offset into the parsed input at which the
+//! problem was found, so bad packets can be logged and counted.
+//!
+//! Encoders that validate their input against protocol limits return an `EncodeError`.
+
+use std::error::Error;
+use std::fmt;
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The address header is missing, truncated or not valid text.
+    Address { offset: usize },
+    /// A digipeater path entry is missing, truncated or not valid text.
+    Path { offset: usize },
+    /// A callsign SSID suffix is not a number.
+    Ssid { offset: usize },
+    /// The information field is missing or malformed.
+    InfoField { offset: usize },
+    /// A position report in the information field is malformed.
+    Position { offset: usize },
+    /// The first byte of the information field is not a known data type identifier.
+    DataType { offset: usize, identifier: u8 },
+}
+
+impl ParseError {
+    /// Byte offset into the parsed input at which the error was found.
+    pub fn offset(&self) -> usize {
+        match *self {
+            ParseError::Address { offset } |
+            ParseError::Path { offset } |
+            ParseError::Ssid { offset } |
+            ParseError::InfoField { offset } |
+            ParseError::Position { offset } |
+            ParseError::DataType { offset, .. } => offset,
+        }
+    }
+
+    /// Move the offset of an error found in a sub-slice so it is relative to the enclosing input.
+    pub(crate) fn shift(self, base: usize) -> ParseError {
+        match self {
+            ParseError::Address { offset } => ParseError::Address { offset: offset + base },
+            ParseError::Path { offset } => ParseError::Path { offset: offset + base },
+            ParseError::Ssid { offset } => ParseError::Ssid { offset: offset + base },
+            ParseError::InfoField { offset } => ParseError::InfoField { offset: offset + base },
+            ParseError::Position { offset } => ParseError::Position { offset: offset + base },
+            ParseError::DataType { offset, identifier } => {
+                ParseError::DataType { offset: offset + base, identifier }
+            },
+        }
+    }
+
+    /// Report an address error found while parsing a digipeater path entry as a path error.
+    pub(crate) fn in_path(self) -> ParseError {
+        match self {
+            ParseError::Address { offset } => ParseError::Path { offset },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Address { offset } => {
+                write!(f, "invalid address at byte {}", offset)
+            },
+            ParseError::Path { offset } => {
+                write!(f, "invalid path at byte {}", offset)
+            },
+            ParseError::Ssid { offset } => {
+                write!(f, "invalid SSID at byte {}", offset)
+            },
+            ParseError::InfoField { offset } => {
+                write!(f, "invalid information field at byte {}", offset)
+            },
+            ParseError::Position { offset } => {
+                write!(f, "invalid position at byte {}", offset)
+            },
+            ParseError::DataType { offset, identifier } => {
+                write!(f, "unknown data type identifier 0x{:02x} at byte {}", identifier, offset)
+            },
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodeError {
+    /// A required field is not set.
+    Missing { field: &'static str },
+    /// A field is shorter than the protocol allows.
+    TooShort { field: &'static str, min: usize },
+    /// A field is longer than the protocol allows.
+    TooLong { field: &'static str, max: usize },
+    /// A field contains a character the protocol does not allow at the given byte offset.
+    InvalidCharacter { field: &'static str, offset: usize },
+    /// A numeric field is outside the range the protocol can represent.
+    OutOfRange { field: &'static str },
+    /// Two fields are set that cannot appear in the same report.
+    Conflict { field: &'static str, other: &'static str },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EncodeError::Missing { field } => {
+                write!(f, "{} is missing", field)
+            },
+            EncodeError::TooShort { field, min } => {
+                write!(f, "{} is shorter than {} characters", field, min)
+            },
+            EncodeError::TooLong { field, max } => {
+                write!(f, "{} is longer than {} characters", field, max)
+            },
+            EncodeError::InvalidCharacter { field, offset } => {
+                write!(f, "invalid character in {} at byte {}", field, offset)
+            },
+            EncodeError::OutOfRange { field } => {
+                write!(f, "{} is out of range", field)
+            },
+            EncodeError::Conflict { field, other } => {
+                write!(f, "{} cannot be combined with {}", field, other)
+            },
+        }
+    }
+}
+
+impl Error for EncodeError {}
@@ -0,0 +1,370 @@
+//! Position data extensions
+//!
+//! The comment of an uncompressed position may start with one seven-byte data extension:
+//!
+//! * `DDD/SSS`: course in degrees and speed in knots, with `000`, dots or spaces for unknown
+//!   values and 360 for due north
+//! * `PHGphgd`: transmitter power, antenna height above average terrain, antenna gain and
+//!   directivity, each as a single code
+//! * `RNGrrrr`: pre-calculated radio range in miles
+//! * `DFSshgd`: direction-finding signal strength in S-points, with the antenna coded as in PHG
+//!
+//! An altitude in feet can also be given anywhere in the comment as `/A=aaaaaa`.
+//!
+//! The `!DAO!` extension, also anywhere in the comment, adds precision to an uncompressed
+//! position. `!Wxy!` gives one more decimal digit of the latitude and longitude minutes, and
+//! `!wxy!` divides the last hundredth of a minute into 91 steps with a base-91 digit each, about
+//! 20 cm. The datum byte is always WGS84.
+//!
+//! Decoding moves these into the `course`, `speed`, `phg`, `range`, `dfs`, `altitude` and `dao`
+//! fields of `PositionFrame` and encoding writes them back, so the comment holds only free text.
+//! Compressed positions carry course and speed, range or altitude in their own bytes; `/A=` is
+//! still used for an altitude that does not fit there.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::functions;
+//! # fn main() {
+//! let position = functions::parse_position(b"!4903.50N/07201.75W-PHG5132/A=001234Home").unwrap();
+//! let phg = position.phg.unwrap();
+//! assert_eq!(phg.power, 25);
+//! assert_eq!(phg.height, 20);
+//! assert_eq!(phg.gain, 3);
+//! assert_eq!(phg.directivity, Some(90));
+//! assert_eq!(position.altitude, Some(1234.0));
+//! assert_eq!(position.comment, b"Home".to_vec());
+//! # }
+//! ```
+
+use std::str;
+
+use structs::PositionFrame;
+use base91;
+
+
+/// Power, height, gain and directivity of a station's antenna.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Phg {
+    /// Transmitter power in watts
+    pub power: u16,
+    /// Antenna height above average terrain in feet
+    pub height: u32,
+    /// Antenna gain in dB
+    pub gain: u8,
+    /// Direction of maximum gain in degrees, or `None` for an omnidirectional antenna
+    pub directivity: Option<u16>,
+}
+
+impl Phg {
+    pub fn new() -> Phg {
+        Phg {
+            power: 0,
+            height: 10,
+            gain: 0,
+            directivity: None,
+        }
+    }
+}
+
+impl Default for Phg {
+    fn default() -> Phg {
+        Phg::new()
+    }
+}
+
+impl Phg {
+    /// Decode the four codes following `PHG`.
+    pub fn decode(codes: &[u8]) -> Option<Phg> {
+        if codes.len() != 4 || !codes[0].is_ascii_digit() {
+            return None;
+        }
+        let (height, gain, directivity) = decode_antenna(&codes[1..])?;
+        let power: u16 = u16::from(codes[0] - b'0');
+        Some(Phg { power: power * power, height, gain, directivity })
+    }
+
+    /// Encode as `PHGphgd`, rounding each value to the nearest code.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded: Vec<u8> = b"PHG".to_vec();
+        encoded.push((f32::from(self.power).sqrt().round() as u8).min(9) + b'0');
+        encoded.extend(encode_antenna(self.height, self.gain, self.directivity));
+        encoded
+    }
+
+    /// Usable radio range in miles, from the formula in the APRS specification.
+    pub fn range(&self) -> f32 {
+        let gain: f32 = 10_f32.powf(f32::from(self.gain) / 10.0);
+        (2.0 * self.height as f32 * (f32::from(self.power) / 10.0 * gain / 2.0).sqrt()).sqrt()
+    }
+}
+
+/// Signal strength and antenna of a direction-finding report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dfs {
+    /// Received signal strength in S-points, 0 to 9
+    pub strength: u8,
+    /// Antenna height above average terrain in feet
+    pub height: u32,
+    /// Antenna gain in dB
+    pub gain: u8,
+    /// Direction of maximum gain in degrees, or `None` for an omnidirectional antenna
+    pub directivity: Option<u16>,
+}
+
+impl Dfs {
+    pub fn new() -> Dfs {
+        Dfs {
+            strength: 0,
+            height: 10,
+            gain: 0,
+            directivity: None,
+        }
+    }
+}
+
+impl Default for Dfs {
+    fn default() -> Dfs {
+        Dfs::new()
+    }
+}
+
+impl Dfs {
+    /// Decode the four codes following `DFS`.
+    pub fn decode(codes: &[u8]) -> Option<Dfs> {
+        if codes.len() != 4 || !codes[0].is_ascii_digit() {
+            return None;
+        }
+        let (height, gain, directivity) = decode_antenna(&codes[1..])?;
+        Some(Dfs { strength: codes[0] - b'0', height, gain, directivity })
+    }
+
+    /// Encode as `DFSshgd`, rounding each value to the nearest code.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded: Vec<u8> = b"DFS".to_vec();
+        encoded.push(self.strength.min(9) + b'0');
+        encoded.extend(encode_antenna(self.height, self.gain, self.directivity));
+        encoded
+    }
+}
+
+/// Form of the `!DAO!` precision extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dao {
+    /// `!Wxy!`: a decimal digit for each coordinate
+    HumanReadable,
+    /// `!wxy!`: a base-91 digit for each coordinate
+    Base91,
+}
+
+impl Dao {
+    // Steps each hundredth of a minute is divided into.
+    fn steps(self) -> u32 {
+        match self {
+            Dao::HumanReadable => 10,
+            Dao::Base91 => base91::BASE,
+        }
+    }
+
+    // Extension of a latitude and longitude, as the `!DAO!` bytes.
+    fn encode(self, lat_steps: u32, lng_steps: u32) -> Vec<u8> {
+        let (datum, offset): (u8, u8) = match self {
+            Dao::HumanReadable => (b'W', b'0'),
+            Dao::Base91 => (b'w', base91::OFFSET),
+        };
+        vec![b'!', datum, lat_steps as u8 + offset, lng_steps as u8 + offset, b'!']
+    }
+
+    // Form and steps of latitude and longitude of the three bytes between the `!`s.
+    fn decode(bytes: &[u8]) -> Option<(Dao, u32, u32)> {
+        let dao: Dao = match bytes[0] {
+            b'W' => Dao::HumanReadable,
+            b'w' => Dao::Base91,
+            _ => return None,
+        };
+        let step = |byt: u8| -> Option<u32> {
+            match dao {
+                Dao::HumanReadable if byt == b' ' => Some(0),
+                Dao::HumanReadable if byt.is_ascii_digit() => Some(u32::from(byt - b'0')),
+                Dao::Base91 if base91::is_digit(byt) => Some(u32::from(byt - base91::OFFSET)),
+                _ => None,
+            }
+        };
+        Some((dao, step(bytes[1])?, step(bytes[2])?))
+    }
+}
+
+/// Move the data extension and `/A=` altitude of a position's comment to their fields.
+///
+/// Weather reports use the `DDD/SSS` position for the wind, so their extension is left alone.
+pub(crate) fn extract_extensions(position: &mut PositionFrame) {
+    if !position.compressed && position.weather.is_none() && position.comment.len() >= 7 {
+        let head: Vec<u8> = position.comment[..7].to_vec();
+        let found: bool = match &head[..3] {
+            b"PHG" => {
+                position.phg = Phg::decode(&head[3..]);
+                position.phg.is_some()
+            },
+            b"DFS" => {
+                position.dfs = Dfs::decode(&head[3..]);
+                position.dfs.is_some()
+            },
+            b"RNG" => {
+                position.range = parse_digits(&head[3..]).map(|range| range as f32);
+                position.range.is_some()
+            },
+            _ if head[3] == b'/' => {
+                match (parse_course_speed(&head[..3]), parse_course_speed(&head[4..])) {
+                    (Some(course), Some(speed)) if course.is_none_or(|course| course <= 360) => {
+                        position.course = course.filter(|course| *course != 0).map(|course| course % 360);
+                        position.speed = speed.map(f32::from);
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            _ => false,
+        };
+        if found {
+            position.comment.drain(..7);
+        }
+    }
+
+    if let Some(start) = position.comment.windows(3).position(|window| window == b"/A=") {
+        let end: usize = start + 9;
+        if let Some(altitude) = position.comment.get((start + 3)..end).and_then(parse_altitude) {
+            position.altitude = Some(altitude as f32);
+            position.comment.drain(start..end);
+        }
+    }
+}
+
+/// Move the `!DAO!` extension of an uncompressed position's comment to its `dao` field, refining
+/// the latitude and longitude unless the position is ambiguous.
+pub(crate) fn extract_dao(position: &mut PositionFrame) {
+    if position.compressed || position.comment.len() < 5 {
+        return;
+    }
+    let found: Option<(usize, (Dao, u32, u32))> = position.comment.windows(5).enumerate()
+        .filter(|&(_, window)| window[0] == b'!' && window[4] == b'!')
+        .filter_map(|(start, window)| Dao::decode(&window[1..4]).map(|dao| (start, dao)))
+        .next();
+    if let Some((start, (dao, lat_steps, lng_steps))) = found {
+        if position.ambiguity == 0 {
+            position.lat = refine(position.lat, lat_steps, dao);
+            position.lng = refine(position.lng, lng_steps, dao);
+        }
+        position.dao = Some(dao);
+        position.comment.drain(start..(start + 5));
+    }
+}
+
+/// `DDMM.MMH` latitude, `DDDMM.MMH` longitude and `!DAO!` bytes of a position with a DAO
+/// extension. The coordinates are truncated to hundredths of a minute so the extension carries
+/// the rest.
+pub(crate) fn create_dao(lat: f32, lng: f32, dao: Dao) -> (String, String, Vec<u8>) {
+    let (lat_hundredths, lat_steps): (u32, u32) = split_minutes(lat, dao);
+    let (lng_hundredths, lng_steps): (u32, u32) = split_minutes(lng, dao);
+    let lat_hemi: char = if lat < 0.0 { 'S' } else { 'N' };
+    let lng_hemi: char = if lng < 0.0 { 'W' } else { 'E' };
+    (format!("{:02}{:02}.{:02}{}", lat_hundredths / 6000, lat_hundredths % 6000 / 100,
+             lat_hundredths % 100, lat_hemi),
+     format!("{:03}{:02}.{:02}{}", lng_hundredths / 6000, lng_hundredths % 6000 / 100,
+             lng_hundredths % 100, lng_hemi),
+     dao.encode(lat_steps, lng_steps))
+}
+
+/// Encode the data extension of an uncompressed position.
+///
+/// Only one extension fits; course and speed take precedence over PHG, range and DFS.
+pub(crate) fn create_extension(position: &PositionFrame) -> Vec<u8> {
+    if position.course.is_some() || position.speed.is_some() {
+        let course: String = match position.course {
+            Some(course) => format!("{:03}", match course % 360 { 0 => 360, course => course }),
+            None => String::from("..."),
+        };
+        let speed: String = match position.speed {
+            Some(speed) => format!("{:03}", speed.round().clamp(0.0, 999.0) as u16),
+            None => String::from("..."),
+        };
+        format!("{}/{}", course, speed).into_bytes()
+    } else if let Some(phg) = position.phg {
+        phg.encode()
+    } else if let Some(range) = position.range {
+        format!("RNG{:04}", range.round().clamp(0.0, 9999.0) as u16).into_bytes()
+    } else if let Some(dfs) = position.dfs {
+        dfs.encode()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Encode an altitude in feet as `/A=aaaaaa`.
+pub(crate) fn create_altitude(altitude: f32) -> Vec<u8> {
+    let altitude: i32 = altitude.round().clamp(-99_999.0, 999_999.0) as i32;
+    format!("/A={:06}", altitude).into_bytes()
+}
+
+// Height, gain and directivity codes shared by PHG and DFS. Heights above 9 are sent as the
+// ASCII characters following `9`.
+fn decode_antenna(codes: &[u8]) -> Option<(u32, u8, Option<u16>)> {
+    let height: u32 = match codes[0] {
+        byt @ b'0'..=b';' => 10 << (byt - b'0'),
+        _ => return None,
+    };
+    if !codes[1].is_ascii_digit() || !codes[2].is_ascii_digit() {
+        return None;
+    }
+    let directivity: Option<u16> = match codes[2] - b'0' {
+        0 => None,
+        9 => return None,
+        code => Some(u16::from(code) * 45 % 360),
+    };
+    Some((height, codes[1] - b'0', directivity))
+}
+
+fn encode_antenna(height: u32, gain: u8, directivity: Option<u16>) -> Vec<u8> {
+    let height: f32 = (height.max(10) as f32 / 10.0).log2().round().min(11.0);
+    let directivity: u8 = match directivity {
+        Some(direction) => match ((f32::from(direction % 360) / 45.0).round() as u8) % 8 {
+            0 => 8,
+            code => code,
+        },
+        None => 0,
+    };
+    vec![height as u8 + b'0', gain.min(9) + b'0', directivity + b'0']
+}
+
+// Move a coordinate away from the equator or prime meridian by a number of DAO steps.
+fn refine(coordinate: f32, steps: u32, dao: Dao) -> f32 {
+    let offset: f64 = f64::from(steps) / f64::from(dao.steps()) / 100.0 / 60.0;
+    let refined: f64 = f64::from(coordinate).abs() + offset;
+    (if coordinate.is_sign_negative() { -refined } else { refined }) as f32
+}
+
+// Whole hundredths of a minute and remaining DAO steps of a coordinate's magnitude.
+fn split_minutes(coordinate: f32, dao: Dao) -> (u32, u32) {
+    let steps: u64 = (f64::from(coordinate).abs() * 60.0 * 100.0 * f64::from(dao.steps())).round() as u64;
+    ((steps / u64::from(dao.steps())) as u32, (steps % u64::from(dao.steps())) as u32)
+}
+
+fn parse_digits(field: &[u8]) -> Option<u32> {
+    if !field.iter().all(|byt| byt.is_ascii_digit()) {
+        return None;
+    }
+    str::from_utf8(field).ok()?.parse().ok()
+}
+
+// A course or speed field: three digits, or dots or spaces when unknown.
+fn parse_course_speed(field: &[u8]) -> Option<Option<u16>> {
+    if field.iter().all(|byt| *byt == b'.' || *byt == b' ') {
+        return Some(None);
+    }
+    parse_digits(field).map(|value| Some(value as u16))
+}
+
+fn parse_altitude(field: &[u8]) -> Option<i32> {
+    match field.split_first() {
+        Some((&b'-', digits)) => parse_digits(digits).map(|value| -(value as i32)),
+        _ => parse_digits(field).map(|value| value as i32),
+    }
+}
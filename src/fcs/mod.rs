@@ -31,16 +31,16 @@ impl FCS {
     }
     pub fn update_bytes(&mut self, bytes: &[u8]) {
         for byte in bytes {
-            for i in (0..8).rev() {
-                // pass each bit of u8 from left->right (true/false)
+            for i in 0..8 {
+                // pass each bit of u8 from right->left (true/false), as AX.25 sends them
                 self.update_bit(((byte >> i) & 0x01_u8) == 1_u8);
             }
         }
     }
     pub fn digest(&self) -> Vec<u8> {
         // Two bytes (u16), little endian
-        let mut ret: Vec<u8> = Vec::new();
-        LittleEndian::write_u16(&mut ret, !self.fcs % ::std::u16::MAX);
+        let mut ret: Vec<u8> = vec![0; 2];
+        LittleEndian::write_u16(&mut ret, !self.fcs);
         ret
     }
 }
@@ -82,7 +82,9 @@ pub fn fcs_validate(bits: Vec<bool>) -> Result<bool, String> {
     let mut _buffer: BitVec = BitVec::new();
     _buffer.extend(buffer.into_iter());
 
-    if fcs.digest() != _buffer.to_bytes() {
+    // the FCS arrives little-bit-endian, as `fcs` appends it
+    let digest: Vec<u8> = fcs.digest().iter().map(|byte| reverse_bits(*byte)).collect();
+    if digest != _buffer.to_bytes() {
         return Err(String::from("Invalid FCS"));
     }
     return Ok(true);
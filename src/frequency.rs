@@ -0,0 +1,281 @@
+//! Frequency specifications
+//!
+//! APRS 1.2 lets the comment of a position, object or item start with a voice frequency, so
+//! repeaters and nets can be found and tuned from the map. The frequency comes first as
+//! `FFF.FFFMHz`, followed by space separated fields in any order:
+//!
+//! * `Tnnn`, `Cnnn` or `Dnnn`: CTCSS tone, CTCSS tone squelch or DCS code, with a lowercase
+//!   letter for a narrow-band channel; `Toff` for no tone and `1750` for a 1750 Hz tone burst
+//! * `+ooo` or `-ooo`: transmit offset in tens of kHz
+//! * `Rnnm` or `Rnnk`: range in miles or kilometres
+//! * `Fnnn.nnn`: a further frequency, such as a linked repeater or an alternative input
+//!
+//! Repeater objects are named after their frequency, `FFF.FFF` and up to two characters to tell
+//! apart repeaters on the same frequency, and use the `/r` symbol.
+//!
+//! Decoding moves the specification into the `frequency` field of `PositionFrame` and encoding
+//! writes it back, so the comment holds only free text. Frequencies are held in kHz.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::frequency::Tone;
+//! # use aprs::functions;
+//! # fn main() {
+//! let position = functions::parse_position(b"!4903.50N/07201.75Wr146.940MHz T100 -060 R25m Net 8pm")
+//!     .unwrap();
+//! let frequency = position.frequency.unwrap();
+//! assert_eq!(frequency.frequency, 146_940);
+//! assert_eq!(frequency.tone, Some(Tone::Ctcss(100)));
+//! assert_eq!(frequency.offset, Some(-600));
+//! assert_eq!(frequency.range, Some(25.0));
+//! assert_eq!(position.comment, b"Net 8pm".to_vec());
+//! # }
+//! ```
+
+use std::str;
+
+use structs::PositionFrame;
+use errors::EncodeError;
+
+
+/// Highest frequency a specification can carry, in kHz.
+pub const MAX_FREQUENCY: u32 = 9_999_990;
+/// Highest frequency a `Fnnn.nnn` field can carry, in kHz.
+pub const MAX_EXTRA_FREQUENCY: u32 = 999_999;
+
+const KM_PER_MILE: f32 = 1.609_344;
+const MAX_OFFSET: i32 = 9_990;
+const MAX_RANGE: f32 = 999.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tone {
+    /// `Tnnn`: CTCSS tone needed to open the repeater, in whole hertz
+    Ctcss(u16),
+    /// `Cnnn`: CTCSS tone squelch, sent and expected on receive, in whole hertz
+    ToneSquelch(u16),
+    /// `Dnnn`: DCS code
+    Dcs(u16),
+    /// `1750`: 1750 Hz tone burst
+    Burst,
+    /// `Toff`: no tone
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frequency {
+    /// Frequency in kHz
+    pub frequency: u32,
+    pub tone: Option<Tone>,
+    /// Narrow-band channel, given by a lowercase tone letter
+    pub narrow: bool,
+    /// Transmit offset in kHz, a multiple of 10
+    pub offset: Option<i32>,
+    /// Range in miles
+    pub range: Option<f32>,
+    /// Further frequencies in kHz, from `Fnnn.nnn` fields
+    pub extra: Vec<u32>,
+}
+
+impl Frequency {
+    pub fn new() -> Frequency {
+        Frequency {
+            frequency: 0,
+            tone: None,
+            narrow: false,
+            offset: None,
+            range: None,
+            extra: Vec::new(),
+        }
+    }
+}
+
+impl Default for Frequency {
+    fn default() -> Frequency {
+        Frequency::new()
+    }
+}
+
+impl Frequency {
+    /// Decode a frequency specification at the start of a comment.
+    ///
+    /// Returns the specification and the number of bytes it took up, including the space
+    /// separating it from the rest of the comment.
+    pub fn decode(comment: &[u8]) -> Option<(Frequency, usize)> {
+        let mhz_end: usize = comment.windows(3).take(10).position(|window| window == b"MHz")?;
+        let mut frequency: Frequency = Frequency::new();
+        frequency.frequency = parse_mhz(&comment[..mhz_end], 1, 4)?;
+        let mut len: usize = mhz_end + 3;
+
+        while comment.get(len) == Some(&b' ') {
+            let start: usize = len + 1;
+            let end: usize = comment[start..].iter().position(|byt| *byt == b' ')
+                .map_or(comment.len(), |pos| start + pos);
+            if !frequency.decode_field(&comment[start..end]) {
+                break;
+            }
+            len = end;
+        }
+        if comment.get(len) == Some(&b' ') {
+            len += 1;
+        }
+        Some((frequency, len))
+    }
+
+    // Set the field a token gives, if it is one not already set.
+    fn decode_field(&mut self, field: &[u8]) -> bool {
+        match field.first() {
+            Some(&b'T') | Some(&b't')
+                if field[1..].eq_ignore_ascii_case(b"off") && self.tone.is_none() => {
+                self.tone = Some(Tone::Off);
+            },
+            Some(&letter)
+                if b"TtCcDd".contains(&letter) && field.len() == 4 && self.tone.is_none() => {
+                let code: u16 = match parse_digits(&field[1..]) {
+                    Some(code) => code as u16,
+                    None => return false,
+                };
+                self.tone = Some(match letter.to_ascii_uppercase() {
+                    b'T' => Tone::Ctcss(code),
+                    b'C' => Tone::ToneSquelch(code),
+                    _ => Tone::Dcs(code),
+                });
+                self.narrow = letter.is_ascii_lowercase();
+            },
+            Some(&b'1') if field == b"1750" && self.tone.is_none() => self.tone = Some(Tone::Burst),
+            Some(&sign)
+                if (sign == b'+' || sign == b'-') && field.len() == 4 && self.offset.is_none() => {
+                let offset: i32 = match parse_digits(&field[1..]) {
+                    Some(offset) => offset as i32 * 10,
+                    None => return false,
+                };
+                self.offset = Some(if sign == b'-' { -offset } else { offset });
+            },
+            Some(&b'R') if (field.len() == 4 || field.len() == 5) && self.range.is_none() => {
+                let range: f32 = match parse_digits(&field[1..(field.len() - 1)]) {
+                    Some(range) => range as f32,
+                    None => return false,
+                };
+                self.range = match field[field.len() - 1] {
+                    b'm' => Some(range),
+                    b'k' => Some(range / KM_PER_MILE),
+                    _ => return false,
+                };
+            },
+            Some(&b'F') if field.len() == 8 => {
+                match parse_mhz(&field[1..], 3, 3) {
+                    Some(extra) => self.extra.push(extra),
+                    None => return false,
+                }
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Encode as `FFF.FFFMHz` and its fields, rounding and limiting each to what it can carry.
+    ///
+    /// Frequencies from 1000 MHz are sent to the nearest 10 kHz.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded: Vec<u8> = format_mhz(self.frequency.min(MAX_FREQUENCY)).into_bytes();
+        encoded.extend(b"MHz");
+        let letter = |upper: char| if self.narrow { upper.to_ascii_lowercase() } else { upper };
+        let tone: Option<String> = match self.tone {
+            Some(Tone::Ctcss(code)) => Some(format!("{}{:03}", letter('T'), code.min(999))),
+            Some(Tone::ToneSquelch(code)) => Some(format!("{}{:03}", letter('C'), code.min(999))),
+            Some(Tone::Dcs(code)) => Some(format!("{}{:03}", letter('D'), code.min(999))),
+            Some(Tone::Burst) => Some(String::from("1750")),
+            Some(Tone::Off) => Some(String::from("Toff")),
+            None => None,
+        };
+        if let Some(tone) = tone {
+            encoded.push(b' ');
+            encoded.extend(tone.as_bytes());
+        }
+        if let Some(offset) = self.offset {
+            let tens: i32 = (offset.clamp(-MAX_OFFSET, MAX_OFFSET) as f32 / 10.0).round() as i32;
+            encoded.extend(format!(" {:+04}", tens).as_bytes());
+        }
+        if let Some(range) = self.range {
+            encoded.extend(format!(" R{:02}m", range.round().clamp(0.0, MAX_RANGE) as u16).as_bytes());
+        }
+        for extra in self.extra.iter() {
+            encoded.extend(format!(" F{}", format_mhz((*extra).min(MAX_EXTRA_FREQUENCY))).as_bytes());
+        }
+        encoded
+    }
+
+    /// Object name for a repeater on this frequency: `FFF.FFF` followed by `suffix`.
+    pub fn object_name(&self, suffix: &str) -> String {
+        format!("{}{}", format_mhz(self.frequency.min(MAX_FREQUENCY)), suffix)
+    }
+}
+
+/// Move the frequency specification at the start of a comment to the `frequency` field.
+pub(crate) fn extract_frequency(position: &mut PositionFrame) {
+    if let Some((frequency, len)) = Frequency::decode(&position.comment) {
+        position.frequency = Some(frequency);
+        position.comment.drain(..len);
+    }
+}
+
+/// Check that the frequency of an object or item can be encoded without change.
+pub(crate) fn check(position: &PositionFrame) -> Result<(), EncodeError> {
+    let frequency: &Frequency = match position.frequency {
+        Some(ref frequency) => frequency,
+        None => return Ok(()),
+    };
+    if frequency.frequency == 0 {
+        return Err(EncodeError::Missing { field: "frequency" });
+    }
+    // Above 1000 MHz only tens of kHz fit
+    let rounded: bool = frequency.frequency >= 1_000_000 && !frequency.frequency.is_multiple_of(10);
+    if frequency.frequency > MAX_FREQUENCY || rounded {
+        return Err(EncodeError::OutOfRange { field: "frequency" });
+    }
+    match frequency.tone {
+        Some(Tone::Ctcss(code)) | Some(Tone::ToneSquelch(code)) | Some(Tone::Dcs(code)) if code > 999 => {
+            return Err(EncodeError::OutOfRange { field: "tone" });
+        },
+        _ => {},
+    }
+    if frequency.offset.is_some_and(|offset| offset.abs() > MAX_OFFSET ||
+                                             !offset.unsigned_abs().is_multiple_of(10)) {
+        return Err(EncodeError::OutOfRange { field: "offset" });
+    }
+    if frequency.range.is_some_and(|range| !(0.0..=MAX_RANGE).contains(&range.round())) {
+        return Err(EncodeError::OutOfRange { field: "range" });
+    }
+    if frequency.extra.iter().any(|extra| *extra > MAX_EXTRA_FREQUENCY) {
+        return Err(EncodeError::OutOfRange { field: "extra frequency" });
+    }
+    Ok(())
+}
+
+// `MMM.kkk` in MHz, with `min_int_digits` to `max_int_digits` digits before the point and up
+// to three after.
+fn parse_mhz(field: &[u8], min_int_digits: usize, max_int_digits: usize) -> Option<u32> {
+    let point: usize = field.iter().position(|byt| *byt == b'.')?;
+    let frac: &[u8] = &field[(point + 1)..];
+    if point < min_int_digits || point > max_int_digits || frac.is_empty() || frac.len() > 3 {
+        return None;
+    }
+    let mhz: u32 = parse_digits(&field[..point])?;
+    let khz: u32 = parse_digits(frac)? * 10_u32.pow(3 - frac.len() as u32);
+    Some(mhz * 1000 + khz)
+}
+
+// Three decimals below 1000 MHz and two above, so the field is always seven characters.
+fn format_mhz(khz: u32) -> String {
+    if khz < 1_000_000 {
+        format!("{:03}.{:03}", khz / 1000, khz % 1000)
+    } else {
+        format!("{}.{:02}", khz / 1000, khz % 1000 / 10)
+    }
+}
+
+fn parse_digits(field: &[u8]) -> Option<u32> {
+    if field.is_empty() || !field.iter().all(|byt| byt.is_ascii_digit()) {
+        return None;
+    }
+    str::from_utf8(field).ok()?.parse().ok()
+}
@@ -2,12 +2,27 @@
 //!
 //! Parse various frame types and return a fully-constructed object. See individual functions for
 //! their descriptions.
+//!
+//! Parsers never panic on malformed input; they return a `ParseError` naming the failed stage and
+//! the byte offset of the problem.
+
+use std::str;
 
-use structs::{Frame, Callsign, InformationField};
+use structs::{Frame, Callsign, InformationField, PositionFrame, CompressionType, NmeaSource};
+use errors::ParseError;
 use constants;
+use geo_util;
+use base91;
+use telemetry;
+use timestamp::Timestamp;
+use weather;
+use extension;
+use area;
+use frequency;
+use payload::DataType;
 
 
-pub fn parse_frame(raw_frame: &[u8]) -> Frame {
+pub fn parse_frame(raw_frame: &[u8]) -> Result<Frame, ParseError> {
     for win in raw_frame.windows(2) {
         if win == constants::ADDR_INFO_DELIM {
             return parse_frame_ax25(raw_frame);
@@ -16,172 +31,333 @@ pub fn parse_frame(raw_frame: &[u8]) -> Frame {
     parse_frame_text(raw_frame)
 }
 
-pub fn parse_frame_text(raw_frame: &[u8]) -> Frame {
+pub fn parse_frame_text(raw_frame: &[u8]) -> Result<Frame, ParseError> {
     // src>dest[,path]:info
-    let src_dest:  usize = raw_frame.iter().position(|x| *x == b'>').unwrap();
-    let path_info: usize = raw_frame.iter().position(|x| *x == b':').unwrap();
+    let src_dest: usize = match raw_frame.iter().position(|x| *x == b'>') {
+        Some(pos) => pos,
+        None => return Err(ParseError::Address { offset: raw_frame.len() }),
+    };
+    let path_info: usize = match raw_frame[src_dest..].iter().position(|x| *x == b':') {
+        Some(pos) => src_dest + pos,
+        None => return Err(ParseError::Address { offset: raw_frame.len() }),
+    };
 
-    let source: Callsign = parse_callsign_text(&raw_frame[..src_dest]);
+    let source: Callsign = parse_callsign_text(&raw_frame[..src_dest])?;
     let mut dest: Callsign = Callsign::new();
-    let info: InformationField = parse_info_field(&raw_frame[(path_info + 1)..]);
+    let info: InformationField = parse_info_field(&raw_frame[(path_info + 1)..])
+        .map_err(|e| e.shift(path_info + 1))?;
 
     // Full path slice
     let mut _path: Vec<Callsign> = Vec::new();
     let _paths: &[u8] = &raw_frame[src_dest + 1..path_info];
+    let mut offset: usize = src_dest + 1;
     for (ind, path) in _paths.split(|x| *x == b',').enumerate() {
         if ind == 0 {
-            dest = parse_callsign_text(path);
+            dest = parse_callsign_text(path).map_err(|e| e.shift(offset))?;
         } else {
-            _path.push(parse_callsign_text(path));
+            _path.push(parse_callsign_text(path).map_err(|e| e.in_path().shift(offset))?);
         }
+        offset += path.len() + 1;
     }
-    Frame {
-        source: source,
+    Ok(Frame {
+        source,
         destination: dest,
         path: _path,
-        info: info
-    }
+        info
+    })
 }
 
-pub fn parse_frame_ax25(raw_frame: &[u8]) -> Frame {
+pub fn parse_frame_ax25(raw_frame: &[u8]) -> Result<Frame, ParseError> {
     let mut _frame: &[u8] = raw_frame;
+    let mut start: usize = 0;
     let mut kiss_call: bool = false;
 
     if _frame.starts_with(&[constants::KISS_DATA_FRAME]) {
-        let mut ind: usize = 0;
-        for (id, byt) in _frame.iter().enumerate() {
-            if byt == &constants::KISS_DATA_FRAME {
-                continue;
-            } else {
-                ind = id;
-                break;
-            }
-        }
-        _frame = &_frame[ind..];
+        start = _frame.iter()
+            .position(|byt| *byt != constants::KISS_DATA_FRAME)
+            .unwrap_or(_frame.len());
+        _frame = &_frame[start..];
         kiss_call = true;
     }
     if _frame.ends_with(&[constants::KISS_DATA_FRAME]) {
-        let mut ind: usize = 0;
-        for (id, byt) in _frame.iter().rev().enumerate() {
-            if byt == &constants::KISS_DATA_FRAME {
-                continue;
-            } else {
-                ind = id;
-                break;
-            }
-        }
+        let ind: usize = _frame.iter()
+            .rev()
+            .position(|byt| *byt != constants::KISS_DATA_FRAME)
+            .unwrap_or(_frame.len());
         _frame = &_frame[..(_frame.len() - ind)];
         kiss_call = true;
     }
 
-    let mut info_pos: usize = 0;
-    for win in _frame.windows(2) {
-        if win == constants::ADDR_INFO_DELIM {
-            break;
-        }
-        info_pos += 1;
-    }
+    let info_pos: usize = match _frame.windows(2).position(|win| win == constants::ADDR_INFO_DELIM) {
+        Some(pos) => pos,
+        None => return Err(ParseError::Address { offset: start + _frame.len() }),
+    };
     let (frame_addressing, _) = _frame.split_at(info_pos);
     let (_, mut info_field)   = _frame.split_at(info_pos + 2);
     if info_field.ends_with(&[0xff, 0x07]) {
         info_field = &info_field[..(info_field.len() - 2)];
     }
-    let dest: Callsign = parse_callsign_ax25(&frame_addressing, kiss_call);
-    let src: Callsign = parse_callsign_ax25(&frame_addressing[7..], kiss_call);
+    if frame_addressing.len() < 14 {
+        return Err(ParseError::Address { offset: start + frame_addressing.len() });
+    }
+    let dest: Callsign = parse_callsign_ax25(frame_addressing, kiss_call)
+        .map_err(|e| e.shift(start))?;
+    let src: Callsign = parse_callsign_ax25(&frame_addressing[7..], kiss_call)
+        .map_err(|e| e.shift(start + 7))?;
 
     let mut path: Vec<Callsign> = Vec::new();
-    for chunk in frame_addressing[14..].chunks(7) {
-        path.push(parse_callsign_ax25(chunk, false));
+    for (ind, chunk) in frame_addressing[14..].chunks(7).enumerate() {
+        let offset: usize = start + 14 + ind * 7;
+        path.push(parse_callsign_ax25(chunk, false).map_err(|e| e.in_path().shift(offset))?);
     }
 
-    Frame {
+    Ok(Frame {
         source: src,
         destination: dest,
-        path: path,
-        info: parse_info_field(info_field),
-    }
+        path,
+        info: parse_info_field(info_field).map_err(|e| e.shift(start + info_pos + 2))?,
+    })
 }
 
-pub fn parse_callsign(raw_callsign: &[u8]) -> Callsign {
+pub fn parse_callsign(raw_callsign: &[u8]) -> Result<Callsign, ParseError> {
     parse_callsign_text(raw_callsign)
 }
 
-pub fn parse_callsign_text(raw_callsign: &[u8]) -> Callsign {
-    let mut _callsign: String = String::from_utf8(raw_callsign.to_vec()).unwrap();
+pub fn parse_callsign_text(raw_callsign: &[u8]) -> Result<Callsign, ParseError> {
+    let mut _callsign: String = match String::from_utf8(raw_callsign.to_vec()) {
+        Ok(call) => call,
+        Err(e) => return Err(ParseError::Address { offset: e.utf8_error().valid_up_to() }),
+    };
     let mut ssid: u8 = 0;
     let mut digi: bool = false;
+    // Offsets in errors count from the start of `raw_callsign`, before any leading `*`
+    let mut start: usize = 0;
 
     if _callsign.contains('*') {
-        _callsign.trim_matches('*');
+        start = _callsign.len() - _callsign.trim_start_matches('*').len();
+        _callsign = _callsign.trim_matches('*').to_string();
         digi = true;
     }
-    match _callsign.find('-') {
-        Some(x) => {
-            _callsign = {
-                let (call, id): (&str, &str) = _callsign.split_at(x);
-                ssid = id[1..].parse::<u8>().unwrap();
-                call.to_string()
+    if let Some(x) = _callsign.find('-') {
+        _callsign = {
+            let (call, id): (&str, &str) = _callsign.split_at(x);
+            ssid = match id[1..].parse::<u8>() {
+                Ok(id) => id,
+                Err(_) => return Err(ParseError::Ssid { offset: start + x + 1 }),
             };
-        },
-        None => {}
+            call.to_string()
+        };
     }
-    Callsign {
-        callsign: _callsign,
-        ssid: ssid,
-        digi: digi
+    if _callsign.is_empty() {
+        return Err(ParseError::Address { offset: 0 });
     }
+    Ok(Callsign {
+        callsign: _callsign,
+        ssid,
+        digi
+    })
 }
 
-pub fn parse_callsign_ax25(raw_callsign: &[u8], kiss_call: bool) -> Callsign {
+pub fn parse_callsign_ax25(raw_callsign: &[u8], kiss_call: bool) -> Result<Callsign, ParseError> {
     // based on ax.25 v2.0 protocol, NOT v2.2
     let mut _callsign: Vec<u8> = Vec::new();
-    let mut digi: bool = false;
 
+    if raw_callsign.len() < 7 {
+        return Err(ParseError::Address { offset: raw_callsign.len() });
+    }
     for chunk in raw_callsign[..6].iter() {
-        let mut _chunk = chunk & 0xFF;
         // shift 1 bit
-        _chunk = _chunk >> 1;
+        let _chunk = chunk >> 1;
         if (_chunk as char).is_alphanumeric() {
             _callsign.push(_chunk);
         }
     }
 
     // seventh byte is ssid or digi
-    let seven_chunk: u8 = raw_callsign[6] & 0xFF;
+    let seven_chunk: u8 = raw_callsign[6];
     let ssid = (seven_chunk >> 1) & 0x0F;
 
-    // fixme - see python module
-    if kiss_call {
-        if (seven_chunk >> 1 & 0x80) == 1 {
-            digi = true;
-        }
-    } else {
-        if (seven_chunk & 0x80) == 1 {
-            digi = true;
-        }
-    }
-    Callsign {
-        callsign: String::from_utf8(_callsign).unwrap(),
-        ssid: ssid,
-        digi: digi
+    // The high bit is the has-been-repeated flag on path entries, but the command/response bit on
+    // the destination and source of a KISS frame.
+    let digi: bool = !kiss_call && (seven_chunk & 0x80) != 0;
+
+    match String::from_utf8(_callsign) {
+        Ok(callsign) => Ok(Callsign {
+            callsign,
+            ssid,
+            digi
+        }),
+        Err(e) => Err(ParseError::Address { offset: e.utf8_error().valid_up_to() }),
     }
 }
 
-pub fn parse_info_field(raw_data: &[u8]) -> InformationField {
-    let first_byte: &u8 = raw_data.first().unwrap();
-    let data_type: String = constants::DATA_TYPE_MAP.get(first_byte).unwrap().to_string();
-    InformationField {
+pub fn parse_info_field(raw_data: &[u8]) -> Result<InformationField, ParseError> {
+    let data_type: DataType = match DataType::from_data(raw_data) {
+        Some(data_type) => data_type,
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    };
+    Ok(InformationField {
         data: raw_data.to_vec(),
-        data_type: data_type,
+        data_type: Some(data_type),
         safe: true
-    }
+    })
 }
 
-pub fn default_data_handler(data: &[u8], data_type: u8) -> InformationField {
-    let _data_type: String = constants::DATA_TYPE_MAP.get(&data_type).unwrap().to_string();
-    InformationField {
+pub fn default_data_handler(data: &[u8], data_type: u8) -> Result<InformationField, ParseError> {
+    Ok(InformationField {
         data: data.to_vec(),
-        data_type: _data_type,
+        data_type: DataType::from_data(&[data_type]),
         safe: false
+    })
+}
+
+/// Parse a `!`, `=`, `/` or `@` position report information field.
+///
+/// The messaging flag is taken from the data type identifier and the seven-character timestamp of
+/// `/` and `@` reports is decoded but not resolved.
+pub fn parse_position(raw_data: &[u8]) -> Result<PositionFrame, ParseError> {
+    let (messaging, timestamped): (bool, bool) = match raw_data.first() {
+        Some(&b'!') => (false, false),
+        Some(&b'=') => (true, false),
+        Some(&b'/') => (false, true),
+        Some(&b'@') => (true, true),
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    };
+    let mut body_start: usize = 1;
+    let mut timestamp: Option<Timestamp> = None;
+
+    if timestamped {
+        timestamp = Some(parse_raw_timestamp(&raw_data[1..]).map_err(|e| e.shift(1))?);
+        body_start += 7;
+    }
+    let mut position: PositionFrame = parse_position_body(&raw_data[body_start..])
+        .map_err(|e| e.shift(body_start))?;
+    position.messaging = messaging;
+    position.timestamp = timestamp;
+    Ok(position)
+}
+
+/// Parse the latitude, symbol table, longitude, symbol code and comment that make up the body of
+/// a position report.
+///
+/// Both the uncompressed `DDMM.MMH` form and the 13-byte base-91 compressed form are accepted.
+/// The returned position has no timestamp and its messaging flag is left at the default. A
+/// compressed telemetry block is moved from the comment to the `telemetry` field, the weather
+/// fields of a report with the weather symbol to the `weather` field, and data extensions, the
+/// `/A=` altitude and the `!DAO!` extension to their own fields.
+pub fn parse_position_body(body: &[u8]) -> Result<PositionFrame, ParseError> {
+    let mut position: PositionFrame = match body.first() {
+        Some(byt) if byt.is_ascii_digit() => parse_uncompressed_body(body)?,
+        Some(_) => parse_compressed_body(body)?,
+        None => return Err(ParseError::Position { offset: 0 }),
+    };
+    if let Some((telemetry, comment)) = telemetry::extract_compressed_telemetry(&position.comment) {
+        position.telemetry = Some(telemetry);
+        position.comment = comment;
+    }
+    weather::extract_position_weather(&mut position);
+    // Before the extensions, as an area can look like course and speed
+    area::extract_area(&mut position);
+    extension::extract_extensions(&mut position);
+    frequency::extract_frequency(&mut position);
+    area::extract_signpost(&mut position);
+    extension::extract_dao(&mut position);
+    Ok(position)
+}
+
+fn parse_uncompressed_body(body: &[u8]) -> Result<PositionFrame, ParseError> {
+    // DDMM.MMH T DDDMM.MMH S comment
+    if body.len() < 19 {
+        return Err(ParseError::Position { offset: body.len() });
+    }
+    let lat: &str = str::from_utf8(&body[..8]).map_err(|_| ParseError::Position { offset: 0 })?;
+    let lng: &str = str::from_utf8(&body[9..18]).map_err(|_| ParseError::Position { offset: 9 })?;
+
+    let mut position: PositionFrame = PositionFrame::new();
+    position.lat = match geo_util::dm2dec_lat(lat) {
+        Some(lat) => lat,
+        None => return Err(ParseError::Position { offset: 0 }),
+    };
+    // The latitude's ambiguity applies to the longitude whatever digits it was sent with
+    let ambiguity: usize = geo_util::ambiguity(lat);
+    if ambiguity > 4 {
+        return Err(ParseError::Position { offset: 0 });
+    }
+    position.lng = match geo_util::dm2dec_lng(&geo_util::ambiguate(lng, ambiguity)) {
+        Some(lng) => lng,
+        None => return Err(ParseError::Position { offset: 9 }),
+    };
+    match body[8] {
+        b'/' | b'\\' | b'0'..=b'9' | b'A'..=b'Z' => position.table = vec![body[8]],
+        _ => return Err(ParseError::Position { offset: 8 }),
+    }
+    position.symbol = body[18];
+    position.ambiguity = ambiguity;
+    position.lat = geo_util::ambiguity_centre(position.lat, ambiguity);
+    position.lng = geo_util::ambiguity_centre(position.lng, ambiguity);
+    position.comment = body[19..].to_vec();
+    Ok(position)
+}
+
+fn parse_compressed_body(body: &[u8]) -> Result<PositionFrame, ParseError> {
+    // T YYYY XXXX S c s t comment
+    if body.len() < 13 {
+        return Err(ParseError::Position { offset: body.len() });
+    }
+    let mut position: PositionFrame = PositionFrame::new();
+    position.compressed = true;
+    position.table = match body[0] {
+        b'/' | b'\\' | b'A'..=b'Z' => vec![body[0]],
+        b'a'..=b'j' => vec![body[0] - b'a' + b'0'],
+        _ => return Err(ParseError::Position { offset: 0 }),
+    };
+    position.lat = match geo_util::decompress_lat(&body[1..5]) {
+        Some(lat) => lat,
+        None => return Err(ParseError::Position { offset: 1 }),
+    };
+    position.lng = match geo_util::decompress_lng(&body[5..9]) {
+        Some(lng) => lng,
+        None => return Err(ParseError::Position { offset: 5 }),
+    };
+    position.symbol = body[9];
+    position.comment = body[13..].to_vec();
+
+    // A space in the course byte means the course/speed and compression type bytes are unused
+    if body[10] == b' ' {
+        return Ok(position);
+    }
+    let comp_type: CompressionType = match CompressionType::from_byte(body[12]) {
+        Some(comp_type) => comp_type,
+        None => return Err(ParseError::Position { offset: 12 }),
+    };
+    position.compression_type = Some(comp_type);
+    if !base91::is_digit(body[10]) {
+        return Err(ParseError::Position { offset: 10 });
+    }
+    if !base91::is_digit(body[11]) {
+        return Err(ParseError::Position { offset: 11 });
+    }
+    let c: u8 = body[10] - base91::OFFSET;
+    let s: u8 = body[11] - base91::OFFSET;
+
+    if comp_type.nmea_source == NmeaSource::Gga {
+        let cs: i32 = i32::from(c) * 91 + i32::from(s);
+        position.altitude = Some(1.002_f32.powi(cs));
+    } else if body[10] == b'{' {
+        position.range = Some(2_f32 * 1.08_f32.powi(i32::from(s)));
+    } else {
+        position.course = Some(u16::from(c) * 4);
+        position.speed = Some(1.08_f32.powi(i32::from(s)) - 1_f32);
+    }
+    Ok(position)
+}
+
+/// Parse the seven-character timestamp of a position report or object.
+pub(crate) fn parse_raw_timestamp(raw_data: &[u8]) -> Result<Timestamp, ParseError> {
+    match Timestamp::parse(raw_data) {
+        Ok(Timestamp::Mdhm { .. }) => Err(ParseError::Position { offset: 6 }),
+        Ok(timestamp) => Ok(timestamp),
+        Err(e) => Err(ParseError::Position { offset: e.offset() }),
     }
 }
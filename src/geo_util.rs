@@ -8,11 +8,20 @@
 //!
 //! The APRS protocol supports coordinate ambiguation by removing decimal precision. This allows an
 //! operator to disclose their general location, such as city or county, without giving away
-//! addresses or private information.
+//! addresses or private information. Decoders place an ambiguous position at the centre of the
+//! area it covers, with `ambiguity_radius` giving the distance to the area's corners.
+//!
+//! Compressed positions carry latitude and longitude as four base-91 digits each instead.
 
 
 use decimaldegrees;
+use base91;
+
 
+// Length of a minute of latitude.
+const METRES_PER_MINUTE: f32 = 1852_f32;
+// Mean radius of the Earth.
+const EARTH_RADIUS_METRES: f64 = 6_371_008.8;
 
 /// Convert a latitude decimal to an APRS-compatible eight-character string.
 ///
@@ -90,3 +99,217 @@ pub fn ambiguate(pos: &str, ambiguity: usize) -> String {
     }
     pos_ambig
 }
+
+/// Convert an APRS latitude string back into a decimal.
+///
+/// Digits blanked by position ambiguity are read as zeros. Returns `None` if the string is not a
+/// valid `DDMM.MMH` latitude.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::dm2dec_lat;
+/// # fn main() {
+/// assert_eq!(dm2dec_lat("3746.44N"), Some(37.774_f32));
+/// assert_eq!(dm2dec_lat("3746.44S"), Some(-37.774_f32));
+/// assert_eq!(dm2dec_lat("3746.4XN"), None);
+/// # }
+/// ```
+pub fn dm2dec_lat(pos: &str) -> Option<f32> {
+    dm2dec(pos, 2, 'N', 'S', 90_f32)
+}
+
+/// Convert an APRS longitude string back into a decimal.
+///
+/// Digits blanked by position ambiguity are read as zeros. Returns `None` if the string is not a
+/// valid `DDDMM.MMH` longitude.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::dm2dec_lng;
+/// # fn main() {
+/// assert_eq!(dm2dec_lng("12225.88W"), Some(-122.431335_f32));
+/// assert_eq!(dm2dec_lng("12225.88N"), None);
+/// # }
+/// ```
+pub fn dm2dec_lng(pos: &str) -> Option<f32> {
+    dm2dec(pos, 3, 'E', 'W', 180_f32)
+}
+
+/// Count the digits blanked out of an APRS coordinate string by `ambiguate`.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::ambiguity;
+/// # fn main() {
+/// assert_eq!(ambiguity("3746.44N"), 0);
+/// assert_eq!(ambiguity("1222 .  W"), 3);
+/// # }
+/// ```
+pub fn ambiguity(pos: &str) -> usize {
+    pos.chars().filter(|chr| *chr == ' ').count()
+}
+
+/// Move a coordinate decoded with its blanked digits read as zeros to the centre of the area
+/// covered by its ambiguity level.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::ambiguity_centre;
+/// # fn main() {
+/// assert_eq!(ambiguity_centre(-49.0, 4), -49.5);
+/// assert_eq!(ambiguity_centre(49.05, 0), 49.05);
+/// # }
+/// ```
+pub fn ambiguity_centre(dec: f32, ambiguity: usize) -> f32 {
+    let half: f32 = ambiguity_minutes(ambiguity) / 2_f32 / 60_f32;
+    if dec.is_sign_negative() { dec - half } else { dec + half }
+}
+
+/// Distance in metres from the centre of an ambiguous position's area to its corners.
+///
+/// Returns 0 for positions without ambiguity.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::ambiguity_radius;
+/// # fn main() {
+/// // One minute of latitude at the equator is a nautical mile
+/// assert!((ambiguity_radius(0.0, 2) - 1852_f32 / 2_f32.sqrt()).abs() < 0.01);
+/// assert_eq!(ambiguity_radius(49.0, 0), 0.0);
+/// # }
+/// ```
+pub fn ambiguity_radius(lat: f32, ambiguity: usize) -> f32 {
+    let half: f32 = ambiguity_minutes(ambiguity) / 2_f32 * METRES_PER_MINUTE;
+    let half_width: f32 = half * lat.to_radians().cos();
+    (half * half + half_width * half_width).sqrt()
+}
+
+/// Great-circle distance in metres between two positions given as decimal degrees.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::distance;
+/// # fn main() {
+/// // One degree of longitude along the equator
+/// assert!((distance(0.0, 0.0, 0.0, 1.0) - 111_195.0).abs() < 1.0);
+/// # }
+/// ```
+pub fn distance(lat1: f32, lng1: f32, lat2: f32, lng2: f32) -> f32 {
+    let (lat1, lat2): (f64, f64) = (f64::from(lat1).to_radians(), f64::from(lat2).to_radians());
+    let dlat: f64 = lat2 - lat1;
+    let dlng: f64 = (f64::from(lng2) - f64::from(lng1)).to_radians();
+    let a: f64 = (dlat / 2_f64).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2_f64).sin().powi(2);
+    (2_f64 * EARTH_RADIUS_METRES * a.sqrt().min(1_f64).asin()) as f32
+}
+
+// Size in minutes of the area covered by an ambiguity level.
+fn ambiguity_minutes(ambiguity: usize) -> f32 {
+    match ambiguity {
+        0 => 0_f32,
+        1 => 0.1,
+        2 => 1_f32,
+        3 => 10_f32,
+        _ => 60_f32,
+    }
+}
+
+fn dm2dec(pos: &str, deg_len: usize, pos_hemi: char, neg_hemi: char, max: f32) -> Option<f32> {
+    if pos.len() != deg_len + 6 || !pos.is_ascii() {
+        return None;
+    }
+    let (digits, hemi): (&str, &str) = pos.split_at(deg_len + 5);
+    let mut cleaned: String = String::new();
+    let mut blanked: bool = false;
+
+    for (ind, chr) in digits.char_indices() {
+        match chr {
+            '.' if ind == deg_len + 2 => cleaned.push(chr),
+            '0'..='9' if !blanked => cleaned.push(chr),
+            ' ' => {
+                blanked = true;
+                cleaned.push('0');
+            },
+            _ => return None,
+        }
+    }
+    let deg: f32 = match cleaned[..deg_len].parse::<f32>() {
+        Ok(deg) => deg,
+        Err(_) => return None,
+    };
+    let min: f32 = match cleaned[deg_len..].parse::<f32>() {
+        Ok(min) if min < 60_f32 => min,
+        _ => return None,
+    };
+    let dec: f32 = decimaldegrees::dm2decimal(deg, min);
+    if dec > max {
+        return None;
+    }
+    match hemi.chars().next() {
+        Some(chr) if chr == pos_hemi => Some(dec),
+        Some(chr) if chr == neg_hemi => Some(-dec),
+        _ => None,
+    }
+}
+
+/// Convert a latitude decimal to the four base-91 digits of a compressed position.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::compress_lat;
+/// # fn main() {
+/// assert_eq!(compress_lat(49.5_f32), b"5L!!".to_vec());
+/// # }
+/// ```
+pub fn compress_lat(dec: f32) -> Vec<u8> {
+    let value: f64 = (380_926_f64 * (90_f64 - f64::from(dec))).floor();
+    base91::encode(value.clamp(0_f64, 68_574_960_f64) as u32, 4).unwrap_or_default()
+}
+
+/// Convert a longitude decimal to the four base-91 digits of a compressed position.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::compress_lng;
+/// # fn main() {
+/// assert_eq!(compress_lng(-72.75_f32), b"<*e7".to_vec());
+/// # }
+/// ```
+pub fn compress_lng(dec: f32) -> Vec<u8> {
+    let value: f64 = (190_463_f64 * (180_f64 + f64::from(dec))).floor();
+    base91::encode(value.clamp(0_f64, 68_566_680_f64) as u32, 4).unwrap_or_default()
+}
+
+/// Convert the four base-91 latitude digits of a compressed position to a decimal.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::decompress_lat;
+/// # fn main() {
+/// assert_eq!(decompress_lat(b"5L!!"), Some(49.5_f32));
+/// # }
+/// ```
+pub fn decompress_lat(digits: &[u8]) -> Option<f32> {
+    if digits.len() != 4 {
+        return None;
+    }
+    let value: u32 = base91::decode(digits)?;
+    Some((90_f64 - f64::from(value) / 380_926_f64) as f32)
+}
+
+/// Convert the four base-91 longitude digits of a compressed position to a decimal.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::geo_util::decompress_lng;
+/// # fn main() {
+/// let lng: f32 = decompress_lng(b"<*e7").unwrap();
+/// assert!((lng - -72.75_f32).abs() < 0.0001);
+/// # }
+/// ```
+pub fn decompress_lng(digits: &[u8]) -> Option<f32> {
+    if digits.len() != 4 {
+        return None;
+    }
+    let value: u32 = base91::decode(digits)?;
+    Some((f64::from(value) / 190_463_f64 - 180_f64) as f32)
+}
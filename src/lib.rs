@@ -24,10 +24,32 @@ extern crate byteorder;
 extern crate bit_vec;
 
 pub mod constants;
+pub mod errors;
+pub mod clock;
+pub mod random;
+pub mod timestamp;
 pub mod util;
 pub mod geo_util;
+pub mod base91;
 pub mod fcs;
 pub mod functions;
 pub mod structs;
+pub mod mice;
+pub mod message;
+pub mod capabilities;
+pub mod query;
+pub mod query_responder;
+pub mod messenger;
+pub mod status;
+pub mod telemetry;
+pub mod weather;
+pub mod extension;
+pub mod area;
+pub mod frequency;
+pub mod nmea;
+pub mod third_party;
+pub mod payload;
+pub mod object;
+pub mod object_manager;
 pub mod decimaldegrees;
 pub mod kiss_structs;
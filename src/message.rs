@@ -0,0 +1,212 @@
+//! APRS messages
+//!
+//! A message information field is a `:`, a nine-character addressee padded with spaces, another
+//! `:` and the message text. The text may end in a `{` and a message number of up to five
+//! characters, which asks the addressee to acknowledge it. Acknowledgements and rejections are
+//! messages whose text is `ack` or `rej` followed by the message number.
+//!
+//! APRS 1.1 reply-acks append the acknowledgement of an earlier message to a new message, as
+//! `{MM}AA`. A bare `{MM}` advertises reply-ack support without acknowledging anything.
+
+use std::str;
+
+use structs::{Frame, InformationField};
+use errors::{ParseError, EncodeError};
+use util::check_printable;
+
+
+/// Maximum length of the message text.
+pub const MAX_TEXT_LEN: usize = 67;
+/// Length of the padded addressee field.
+pub const ADDRESSEE_LEN: usize = 9;
+/// Maximum length of a message number.
+pub const MAX_ID_LEN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageKind {
+    Message,
+    Ack,
+    Rej,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    /// Addressee with the padding removed
+    pub addressee: String,
+    pub kind: MessageKind,
+    /// Message text; empty for acks and rejects
+    pub text: String,
+    /// Message number to acknowledge, or the number an ack or reject refers to
+    pub id: Option<String>,
+    /// Acknowledgement of an earlier message carried in a reply-ack. `Some("")` when the sender
+    /// only advertises reply-ack support.
+    pub reply_ack: Option<String>,
+}
+
+impl Message {
+    pub fn new() -> Message {
+        Message {
+            addressee: String::new(),
+            kind: MessageKind::Message,
+            text: String::new(),
+            id: None,
+            reply_ack: None,
+        }
+    }
+}
+
+impl Default for Message {
+    fn default() -> Message {
+        Message::new()
+    }
+}
+
+impl Message {
+    /// Build an acknowledgement of a received message.
+    pub fn ack(addressee: &str, id: &str) -> Message {
+        Message {
+            addressee: addressee.to_string(),
+            kind: MessageKind::Ack,
+            id: Some(id.to_string()),
+            ..Message::new()
+        }
+    }
+
+    /// Build a rejection of a received message.
+    pub fn rej(addressee: &str, id: &str) -> Message {
+        Message {
+            addressee: addressee.to_string(),
+            kind: MessageKind::Rej,
+            id: Some(id.to_string()),
+            ..Message::new()
+        }
+    }
+
+    /// Decode the message carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Message, ParseError> {
+        Message::from_info_field(&frame.info)
+    }
+
+    /// Decode a `:` information field into a message.
+    pub fn from_info_field(info: &InformationField) -> Result<Message, ParseError> {
+        parse_message(&info.data)
+    }
+
+    /// Encode as a `:` information field.
+    ///
+    /// The addressee may be at most nine characters, the text at most 67 characters without `|`,
+    /// `~` or `{`, and message numbers at most five alphanumeric characters.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        if self.addressee.len() > ADDRESSEE_LEN {
+            return Err(EncodeError::TooLong { field: "addressee", max: ADDRESSEE_LEN });
+        }
+        check_printable(self.addressee.as_bytes(), b":", "addressee")?;
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b':');
+        info.extend(format!("{:<9}", self.addressee).as_bytes());
+        info.push(b':');
+
+        match self.kind {
+            MessageKind::Ack | MessageKind::Rej => {
+                info.extend(if self.kind == MessageKind::Ack { b"ack" } else { b"rej" });
+                match self.id {
+                    Some(ref id) => info.extend(validate_id(id, "message number")?.as_bytes()),
+                    None => return Err(EncodeError::Missing { field: "message number" }),
+                }
+            },
+            MessageKind::Message => {
+                if self.text.len() > MAX_TEXT_LEN {
+                    return Err(EncodeError::TooLong { field: "text", max: MAX_TEXT_LEN });
+                }
+                check_printable(self.text.as_bytes(), b"|~{", "text")?;
+                info.extend(self.text.as_bytes());
+                if let Some(ref id) = self.id {
+                    info.push(b'{');
+                    info.extend(validate_id(id, "message number")?.as_bytes());
+                    if let Some(ref reply_ack) = self.reply_ack {
+                        info.push(b'}');
+                        if !reply_ack.is_empty() {
+                            info.extend(validate_id(reply_ack, "reply-ack")?.as_bytes());
+                        }
+                    }
+                }
+            },
+        }
+        Ok(info)
+    }
+}
+
+/// Parse a `:` message information field.
+pub fn parse_message(raw_data: &[u8]) -> Result<Message, ParseError> {
+    match raw_data.first() {
+        Some(&b':') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    if raw_data.len() < ADDRESSEE_LEN + 2 {
+        return Err(ParseError::InfoField { offset: raw_data.len() });
+    }
+    if raw_data[ADDRESSEE_LEN + 1] != b':' {
+        return Err(ParseError::InfoField { offset: ADDRESSEE_LEN + 1 });
+    }
+    let addressee: &str = str::from_utf8(&raw_data[1..(ADDRESSEE_LEN + 1)])
+        .map_err(|e| ParseError::InfoField { offset: 1 + e.valid_up_to() })?;
+    let text_start: usize = ADDRESSEE_LEN + 2;
+    let text: &str = str::from_utf8(&raw_data[text_start..])
+        .map_err(|e| ParseError::InfoField { offset: text_start + e.valid_up_to() })?;
+    // Some stations terminate the text with a line ending
+    let text: &str = text.trim_end_matches(&['\r', '\n'][..]);
+
+    let mut message: Message = Message::new();
+    message.addressee = addressee.trim_end().to_string();
+
+    for &(prefix, kind) in &[("ack", MessageKind::Ack), ("rej", MessageKind::Rej)] {
+        if !text.starts_with(prefix) {
+            continue;
+        }
+        let (id, reply_ack): (&str, Option<&str>) = split_reply_ack(text[3..].trim_end());
+        if valid_id(id) {
+            message.kind = kind;
+            message.id = Some(id.to_string());
+            message.reply_ack = reply_ack.map(|ack| ack.to_string());
+            return Ok(message);
+        }
+    }
+
+    if let Some(pos) = text.rfind('{') {
+        let (id, reply_ack): (&str, Option<&str>) = split_reply_ack(text[(pos + 1)..].trim_end());
+        if valid_id(id) {
+            message.text = text[..pos].to_string();
+            message.id = Some(id.to_string());
+            message.reply_ack = reply_ack.map(|ack| ack.to_string());
+            return Ok(message);
+        }
+    }
+    message.text = text.to_string();
+    Ok(message)
+}
+
+// Split `MM}AA` into the message number and the reply-ack.
+fn split_reply_ack(id: &str) -> (&str, Option<&str>) {
+    match id.find('}') {
+        Some(pos) => (&id[..pos], Some(&id[(pos + 1)..])),
+        None => (id, None),
+    }
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_ID_LEN && id.bytes().all(|byt| byt.is_ascii_alphanumeric())
+}
+
+fn validate_id<'a>(id: &'a str, field: &'static str) -> Result<&'a str, EncodeError> {
+    if id.is_empty() {
+        return Err(EncodeError::Missing { field });
+    }
+    if id.len() > MAX_ID_LEN {
+        return Err(EncodeError::TooLong { field, max: MAX_ID_LEN });
+    }
+    match id.bytes().position(|byt| !byt.is_ascii_alphanumeric()) {
+        Some(pos) => Err(EncodeError::InvalidCharacter { field, offset: pos }),
+        None => Ok(id),
+    }
+}
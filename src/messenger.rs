@@ -0,0 +1,262 @@
+//! Reliable message delivery
+//!
+//! The `Messenger` numbers outgoing messages per addressee and retransmits them on a decaying
+//! schedule until they are acknowledged, rejected or run out of attempts. Incoming messages
+//! addressed to our callsign are acknowledged automatically and duplicates are suppressed.
+//!
+//! The messenger does no I/O itself. Feed it every received frame with `receive`, call `poll`
+//! regularly, transmit the frames both return, and drain the outcome with `events`.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use clock::Clock;
+use constants;
+use errors::EncodeError;
+use message::{Message, MessageKind};
+use structs::{Frame, Callsign, InformationField};
+
+
+/// Outcome of sending or receiving a message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessengerEvent {
+    /// An outgoing message was acknowledged.
+    Delivered { addressee: String, id: String },
+    /// An outgoing message was rejected.
+    Rejected { addressee: String, id: String },
+    /// An outgoing message ran out of attempts without an answer.
+    TimedOut { addressee: String, id: String },
+    /// A new message addressed to our callsign arrived.
+    Received { source: Callsign, message: Message },
+}
+
+struct Outgoing {
+    addressee: String,
+    id: String,
+    frame: Frame,
+    attempts: usize,
+    next_attempt: Duration,
+}
+
+pub struct Messenger<C: Clock> {
+    callsign: Callsign,
+    destination: Callsign,
+    path: Vec<Callsign>,
+    clock: C,
+    retry_interval: Duration,
+    max_retry_interval: Duration,
+    max_attempts: usize,
+    duplicate_window: Duration,
+    next_id: HashMap<String, u32>,
+    outgoing: Vec<Outgoing>,
+    // (source, message number, time received)
+    seen: VecDeque<(String, String, Duration)>,
+    events: VecDeque<MessengerEvent>,
+}
+
+impl<C: Clock> Messenger<C> {
+    /// Create a messenger for our callsign.
+    ///
+    /// Messages are sent to the default tocall with no path. Retries start 30 seconds after the
+    /// first transmission and double each time up to 30 minutes, for five transmissions in total.
+    /// Duplicates are suppressed for 30 minutes.
+    pub fn new(callsign: Callsign, clock: C) -> Messenger<C> {
+        let mut destination: Callsign = Callsign::new();
+        destination.set_callsign(constants::DEFAULT_TOCALL.to_string());
+        Messenger {
+            callsign,
+            destination,
+            path: Vec::new(),
+            clock,
+            retry_interval: Duration::from_secs(30),
+            max_retry_interval: Duration::from_secs(30 * 60),
+            max_attempts: 5,
+            duplicate_window: Duration::from_secs(30 * 60),
+            next_id: HashMap::new(),
+            outgoing: Vec::new(),
+            seen: VecDeque::new(),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl<C: Clock> Messenger<C> {
+    pub fn set_destination(&mut self, destination: Callsign) {
+        self.destination = destination;
+    }
+    pub fn set_path(&mut self, path: Vec<Callsign>) {
+        self.path = path;
+    }
+    /// Delay before the first retry. Each following retry waits twice as long as the last, up to
+    /// the maximum retry interval.
+    pub fn set_retry_interval(&mut self, interval: Duration) {
+        self.retry_interval = interval;
+    }
+    /// Longest delay between retries, however often the delay has doubled.
+    pub fn set_max_retry_interval(&mut self, interval: Duration) {
+        self.max_retry_interval = interval;
+    }
+    /// Number of transmissions, including the first, before a message times out.
+    pub fn set_max_attempts(&mut self, attempts: usize) {
+        self.max_attempts = attempts;
+    }
+    /// How long a received message number is remembered for duplicate suppression.
+    pub fn set_duplicate_window(&mut self, window: Duration) {
+        self.duplicate_window = window;
+    }
+
+    /// Number of messages still waiting for an answer.
+    pub fn pending(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    /// Queue a message for delivery and return its message number.
+    ///
+    /// The message is transmitted by the next call to `poll`.
+    pub fn send(&mut self, addressee: &str, text: &str) -> Result<String, EncodeError> {
+        let key: String = addressee.to_uppercase();
+        let id: u32 = *self.next_id.get(&key).unwrap_or(&1);
+
+        let mut message: Message = Message::new();
+        message.addressee = addressee.to_string();
+        message.text = text.to_string();
+        message.id = Some(id.to_string());
+        let frame: Frame = self.create_frame(&message)?;
+
+        self.next_id.insert(key, if id >= 99_999 { 1 } else { id + 1 });
+        self.outgoing.push(Outgoing {
+            addressee: addressee.to_string(),
+            id: id.to_string(),
+            frame,
+            attempts: 0,
+            next_attempt: self.clock.now(),
+        });
+        Ok(id.to_string())
+    }
+
+    /// Return the frames due for transmission and time out messages that ran out of attempts.
+    pub fn poll(&mut self) -> Vec<Frame> {
+        let now: Duration = self.clock.now();
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut ind: usize = 0;
+
+        while ind < self.outgoing.len() {
+            if self.outgoing[ind].next_attempt > now {
+                ind += 1;
+                continue;
+            }
+            if self.outgoing[ind].attempts >= self.max_attempts {
+                let expired: Outgoing = self.outgoing.remove(ind);
+                self.events.push_back(MessengerEvent::TimedOut {
+                    addressee: expired.addressee,
+                    id: expired.id,
+                });
+                continue;
+            }
+            let max_delay: Duration = self.max_retry_interval;
+            let pending: &mut Outgoing = &mut self.outgoing[ind];
+            let backoff: u32 = 1 << pending.attempts.min(16);
+            pending.attempts += 1;
+            let delay: Duration = self.retry_interval.checked_mul(backoff)
+                .map_or(max_delay, |delay| delay.min(max_delay));
+            pending.next_attempt = now.saturating_add(delay);
+            frames.push(pending.frame.clone());
+            ind += 1;
+        }
+        frames
+    }
+
+    /// Process a received frame and return any acknowledgement to transmit.
+    ///
+    /// Frames that are not messages addressed to our callsign are ignored.
+    pub fn receive(&mut self, frame: &Frame) -> Vec<Frame> {
+        let message: Message = match Message::from_frame(frame) {
+            Ok(message) => message,
+            Err(_) => return Vec::new(),
+        };
+        if !message.addressee.eq_ignore_ascii_case(&self.callsign.to_string()) {
+            return Vec::new();
+        }
+        let source: String = frame.source.to_string();
+
+        match message.kind {
+            MessageKind::Ack | MessageKind::Rej => {
+                if let Some(ref id) = message.id {
+                    self.resolve(&source, id, message.kind == MessageKind::Ack);
+                }
+                Vec::new()
+            },
+            MessageKind::Message => {
+                if let Some(ref ack) = message.reply_ack {
+                    if !ack.is_empty() {
+                        self.resolve(&source, ack, true);
+                    }
+                }
+                let id: String = match message.id {
+                    Some(ref id) => id.clone(),
+                    None => {
+                        self.events.push_back(MessengerEvent::Received {
+                            source: frame.source.clone(),
+                            message,
+                        });
+                        return Vec::new();
+                    },
+                };
+                if !self.is_duplicate(&source, &id) {
+                    self.events.push_back(MessengerEvent::Received {
+                        source: frame.source.clone(),
+                        message,
+                    });
+                }
+                // Acknowledge duplicates too, in case our earlier ack was lost
+                match self.create_frame(&Message::ack(&source, &id)) {
+                    Ok(ack) => vec![ack],
+                    Err(_) => Vec::new(),
+                }
+            },
+        }
+    }
+
+    /// Take the events produced since the last call.
+    pub fn events(&mut self) -> Vec<MessengerEvent> {
+        self.events.drain(..).collect()
+    }
+
+    fn resolve(&mut self, source: &str, id: &str, delivered: bool) {
+        let pos: Option<usize> = self.outgoing.iter().position(|pending| {
+            pending.addressee.eq_ignore_ascii_case(source) && pending.id == id
+        });
+        if let Some(pos) = pos {
+            let done: Outgoing = self.outgoing.remove(pos);
+            self.events.push_back(if delivered {
+                MessengerEvent::Delivered { addressee: done.addressee, id: done.id }
+            } else {
+                MessengerEvent::Rejected { addressee: done.addressee, id: done.id }
+            });
+        }
+    }
+
+    fn is_duplicate(&mut self, source: &str, id: &str) -> bool {
+        let now: Duration = self.clock.now();
+        let window: Duration = self.duplicate_window;
+        self.seen.retain(|&(_, _, time)| now < time.saturating_add(window));
+
+        if self.seen.iter().any(|(src, seen_id, _)| src == source && seen_id == id) {
+            return true;
+        }
+        self.seen.push_back((source.to_string(), id.to_string(), now));
+        false
+    }
+
+    fn create_frame(&self, message: &Message) -> Result<Frame, EncodeError> {
+        let mut info: InformationField = InformationField::new();
+        info.set_data(&message.create_info_field()?);
+        info.set_safe(true);
+        Ok(Frame {
+            source: self.callsign.clone(),
+            destination: self.destination.clone(),
+            path: self.path.clone(),
+            info,
+        })
+    }
+}
@@ -0,0 +1,402 @@
+//! Mic-E position reports
+//!
+//! Mic-E packs a position report into the destination callsign and the first nine bytes of the
+//! information field. The destination carries the latitude, the three message bits and the
+//! north/south, longitude offset and east/west flags. The information field carries longitude,
+//! speed, course and symbol, followed by an optional altitude and a status text that may be
+//! wrapped in a radio or manufacturer type code.
+//!
+//! `MicE::encode` reverses the process, producing the destination callsign and information field
+//! to put into an outgoing `Frame`.
+//!
+//! See chapter 10 of the [APRS protocol reference][1] and the [Mic-E type codes][2].
+//!
+//! [1]: http://www.aprs.org/doc/APRS101.PDF
+//! [2]: http://www.aprs.org/aprs12/mic-e-types.txt
+
+use structs::{Frame, PositionFrame, Callsign};
+use errors::ParseError;
+use geo_util;
+use base91;
+use telemetry;
+
+
+/// Mic-E message code, taken from the three message bits of the destination callsign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MicEMessage {
+    OffDuty,
+    EnRoute,
+    InService,
+    Returning,
+    Committed,
+    Special,
+    Priority,
+    /// Custom message `C0` to `C6`
+    Custom(u8),
+    Emergency,
+    /// Standard and custom message bits were mixed
+    Unknown,
+}
+
+/// Decoded Mic-E report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MicE {
+    /// Position, symbol, course, speed, altitude and status text. Speed is in knots and altitude
+    /// in feet, as for every other position.
+    pub position: PositionFrame,
+    pub message: MicEMessage,
+    /// `true` for the current `` ` `` format, `false` for the old `'` format.
+    pub current: bool,
+    /// Radio or manufacturer identified by the status text type code.
+    pub radio: Option<&'static str>,
+}
+
+// Radios identified by the leading type byte and an optional trailing byte.
+static KENWOOD_TYPES: &[(u8, Option<u8>, &str)] = &[
+    (b'>', Some(b'='), "Kenwood TH-D72"),
+    (b'>', Some(b'^'), "Kenwood TH-D74"),
+    (b'>', None, "Kenwood TH-D7A"),
+    (b']', Some(b'='), "Kenwood TM-D710"),
+    (b']', None, "Kenwood TM-D700"),
+];
+
+// Radios identified by the leading type byte and a trailing two-byte suffix.
+static SUFFIX_TYPES: &[(u8, &[u8; 2], &str)] = &[
+    (b'`', b"_ ", "Yaesu VX-8"),
+    (b'`', b"_\"", "Yaesu FTM-350"),
+    (b'`', b"_#", "Yaesu VX-8G"),
+    (b'`', b"_$", "Yaesu FT1D"),
+    (b'`', b"_%", "Yaesu FTM-400DR"),
+    (b'`', b"_)", "Yaesu FTM-100D"),
+    (b'`', b"_(", "Yaesu FT2D"),
+    (b'`', b"_0", "Yaesu FT3D"),
+    (b'`', b"_3", "Yaesu FT5D"),
+    (b'`', b"_1", "Yaesu FTM-300D"),
+    (b'`', b" X", "SainSonic AP510"),
+    (b'\'', b"|3", "Byonics TinyTrack3"),
+    (b'\'', b"|4", "Byonics TinyTrack4"),
+    (b'\'', b":4", "SCS P4dragon DR-7400"),
+    (b'\'', b":8", "SCS P4dragon DR-7800"),
+];
+
+
+impl MicEMessage {
+    /// Decode the three message bits and whether they were sent as custom bits.
+    pub fn from_bits(bits: u8, custom: bool) -> MicEMessage {
+        match (bits & 0x07, custom) {
+            (0, _) => MicEMessage::Emergency,
+            (bits, true) => MicEMessage::Custom(7 - bits),
+            (7, false) => MicEMessage::OffDuty,
+            (6, false) => MicEMessage::EnRoute,
+            (5, false) => MicEMessage::InService,
+            (4, false) => MicEMessage::Returning,
+            (3, false) => MicEMessage::Committed,
+            (2, false) => MicEMessage::Special,
+            (_, false) => MicEMessage::Priority,
+        }
+    }
+}
+
+impl MicEMessage {
+    /// Encode as the three message bits and whether they are sent as custom bits.
+    ///
+    /// `Unknown` is sent as a mix of custom and standard bits so it decodes as `Unknown` again.
+    fn to_bits(self) -> (u8, bool) {
+        match self {
+            MicEMessage::OffDuty => (7, false),
+            MicEMessage::EnRoute => (6, false),
+            MicEMessage::InService => (5, false),
+            MicEMessage::Returning => (4, false),
+            MicEMessage::Committed => (3, false),
+            MicEMessage::Special => (2, false),
+            MicEMessage::Priority => (1, false),
+            MicEMessage::Custom(num) => (7 - num.min(6), true),
+            MicEMessage::Emergency => (0, false),
+            MicEMessage::Unknown => (6, true),
+        }
+    }
+}
+
+impl MicE {
+    pub fn new(position: PositionFrame, message: MicEMessage) -> MicE {
+        MicE {
+            position,
+            message,
+            current: true,
+            radio: None,
+        }
+    }
+
+    /// Encode the destination callsign and information field of a Mic-E report.
+    ///
+    /// Latitude, longitude, ambiguity, symbol, course, speed (knots), altitude (feet) and comment
+    /// are taken from the position. The radio type code is not sent.
+    pub fn encode(&self) -> (Callsign, Vec<u8>) {
+        (self.create_destination(), self.create_info_field())
+    }
+
+    /// Encode the latitude, message bits and flags as a destination callsign.
+    pub fn create_destination(&self) -> Callsign {
+        let lat: String = geo_util::ambiguate(&geo_util::dec2dm_lat(self.position.lat),
+                                              self.position.ambiguity.min(4));
+        let lng: String = geo_util::dec2dm_lng(self.position.lng);
+        let lng_deg: u16 = lng[..3].parse::<u16>().unwrap_or(0);
+        let (msg_bits, custom): (u8, bool) = self.message.to_bits();
+        let flags: [bool; 3] = [
+            lat.ends_with('N'),
+            !(10..100).contains(&lng_deg),
+            lng.ends_with('W'),
+        ];
+
+        let mut callsign: String = String::new();
+        for (ind, chr) in lat.chars().filter(|chr| *chr != '.').take(6).enumerate() {
+            let digit: u8 = chr as u8;
+            // Only the first custom bit is sent as custom for Unknown, so it stays mixed
+            let (set, custom): (bool, bool) = if ind < 3 {
+                (msg_bits & (0x04 >> ind) != 0,
+                 custom && (self.message != MicEMessage::Unknown || ind == 0))
+            } else {
+                (flags[ind - 3], false)
+            };
+            let encoded: u8 = match (digit, set, custom) {
+                (b' ', false, _) => b'L',
+                (b' ', true, true) => b'K',
+                (b' ', true, false) => b'Z',
+                (digit, false, _) => digit,
+                (digit, true, true) => digit - b'0' + b'A',
+                (digit, true, false) => digit - b'0' + b'P',
+            };
+            callsign.push(encoded as char);
+        }
+
+        let mut destination: Callsign = Callsign::new();
+        destination.set_callsign(callsign);
+        destination
+    }
+
+    /// Encode longitude, speed, course, symbol, altitude and comment as an information field.
+    pub fn create_info_field(&self) -> Vec<u8> {
+        // The latitude ambiguity also covers the longitude, whose blanked digits are sent as zeros
+        let lng: String = geo_util::ambiguate(&geo_util::dec2dm_lng(self.position.lng),
+                                              self.position.ambiguity.min(4)).replace(' ', "0");
+        let deg: u16 = lng[..3].parse::<u16>().unwrap_or(0);
+        let min: u8 = lng[3..5].parse::<u8>().unwrap_or(0);
+        let hun: u8 = lng[6..8].parse::<u8>().unwrap_or(0);
+
+        let mut info: Vec<u8> = Vec::new();
+        info.push(if self.current { b'`' } else { b'\'' });
+        info.push(match deg {
+            0..=9 => deg + 118,
+            10..=99 => deg + 28,
+            100..=109 => deg + 8,
+            _ => deg - 72,
+        } as u8);
+        info.push(if min < 10 { min + 88 } else { min + 28 });
+        info.push(hun + 28);
+
+        let speed: u16 = self.position.speed.unwrap_or(0_f32).round().clamp(0_f32, 799_f32) as u16;
+        let course: u16 = self.position.course.unwrap_or(0) % 361;
+        let sp: u16 = speed / 10;
+        let mut dc: u16 = (speed % 10) * 10 + course / 100;
+        // APRS101 offsets speeds under 200 knots by 80 and course hundreds of 0 by 400 in SP and
+        // DC; SE is always course % 100 + 28
+        info.push(if sp < 20 { sp + 108 } else { sp + 28 } as u8);
+        if dc < 4 {
+            dc += 4;
+        }
+        info.push((dc + 28) as u8);
+        info.push((course % 100 + 28) as u8);
+
+        info.push(self.position.symbol);
+        info.push(self.position.table.first().cloned().unwrap_or(b'/'));
+        if let Some(altitude) = self.position.altitude {
+            let alt: f32 = (altitude / 3.280_84_f32).round() + 10_000_f32;
+            if let Some(alt) = base91::encode(alt.max(0_f32) as u32, 3) {
+                info.extend(alt);
+                info.push(b'}');
+            }
+        }
+        info.extend(&self.position.comment);
+        if let Some(ref telemetry) = self.position.telemetry {
+            info.extend(telemetry.encode());
+        }
+
+        info
+    }
+
+    /// Decode a Mic-E report from the destination callsign and information field of a frame.
+    ///
+    /// Errors in the destination callsign are reported as `ParseError::Address` with the offset
+    /// into the callsign; errors in the information field as `ParseError::Position` with the
+    /// offset into the information field.
+    pub fn from_frame(frame: &Frame) -> Result<MicE, ParseError> {
+        let data: &[u8] = &frame.info.data;
+        let current: bool = match data.first() {
+            Some(&b'`') | Some(&0x1c) => true,
+            Some(&b'\'') | Some(&0x1d) => false,
+            Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+            None => return Err(ParseError::InfoField { offset: 0 }),
+        };
+        let dest: &[u8] = frame.destination.callsign.as_bytes();
+        if dest.len() != 6 {
+            return Err(ParseError::Address { offset: dest.len() });
+        }
+        if data.len() < 9 {
+            return Err(ParseError::Position { offset: data.len() });
+        }
+
+        // Destination: latitude digits, message bits and flags
+        let mut lat_digits: Vec<u8> = Vec::new();
+        let mut msg_bits: u8 = 0;
+        let mut std_bits: bool = false;
+        let mut custom_bits: bool = false;
+        let mut flags: Vec<bool> = Vec::new();
+        for (ind, byt) in dest.iter().enumerate() {
+            let (digit, bit, custom, flag): (u8, bool, bool, bool) = match *byt {
+                b'0'..=b'9' => (*byt, false, false, false),
+                b'A'..=b'J' => (byt - b'A' + b'0', true, true, false),
+                b'K' => (b' ', true, true, false),
+                b'L' => (b' ', false, false, false),
+                b'P'..=b'Y' => (byt - b'P' + b'0', true, false, true),
+                b'Z' => (b' ', true, false, true),
+                _ => return Err(ParseError::Address { offset: ind }),
+            };
+            lat_digits.push(digit);
+            if ind < 3 {
+                msg_bits = (msg_bits << 1) | bit as u8;
+                std_bits |= bit && !custom;
+                custom_bits |= custom;
+            } else {
+                flags.push(flag);
+            }
+        }
+        let message: MicEMessage = if std_bits && custom_bits {
+            MicEMessage::Unknown
+        } else {
+            MicEMessage::from_bits(msg_bits, custom_bits)
+        };
+        let (north, lng_offset, west): (bool, bool, bool) = (flags[0], flags[1], flags[2]);
+
+        let mut lat: String = String::from_utf8_lossy(&lat_digits).into_owned();
+        lat.insert(4, '.');
+        lat.push(if north { 'N' } else { 'S' });
+        let ambiguity: usize = geo_util::ambiguity(&lat);
+
+        let mut position: PositionFrame = PositionFrame::new();
+        position.source = frame.source.clone();
+        position.destination = frame.destination.clone();
+        position.path = frame.path.clone();
+        position.info = frame.info.clone();
+        position.messaging = true;
+        position.ambiguity = ambiguity;
+        position.lat = match geo_util::dm2dec_lat(&lat) {
+            Some(lat) => lat,
+            None => return Err(ParseError::Address { offset: 0 }),
+        };
+
+        // Information field: longitude, speed, course and symbol
+        for (ind, byt) in data[1..9].iter().enumerate() {
+            if *byt < 28 || *byt > 127 {
+                return Err(ParseError::Position { offset: ind + 1 });
+            }
+        }
+        let mut lng_deg: u16 = u16::from(data[1] - 28);
+        if lng_offset {
+            lng_deg += 100;
+        }
+        if (180..=189).contains(&lng_deg) {
+            lng_deg -= 80;
+        } else if (190..=199).contains(&lng_deg) {
+            lng_deg -= 190;
+        }
+        let mut lng_min: u8 = data[2] - 28;
+        if lng_min >= 60 {
+            lng_min -= 60;
+        }
+        let lng_hun: u8 = data[3] - 28;
+        if lng_deg > 179 {
+            return Err(ParseError::Position { offset: 1 });
+        }
+        if lng_min > 59 {
+            return Err(ParseError::Position { offset: 2 });
+        }
+        if lng_hun > 99 {
+            return Err(ParseError::Position { offset: 3 });
+        }
+        let lng: String = format!("{:03}{:02}.{:02}{}", lng_deg, lng_min, lng_hun,
+                                  if west { 'W' } else { 'E' });
+        position.lng = match geo_util::dm2dec_lng(&geo_util::ambiguate(&lng, ambiguity)) {
+            Some(lng) => lng,
+            None => return Err(ParseError::Position { offset: 1 }),
+        };
+        position.lat = geo_util::ambiguity_centre(position.lat, ambiguity);
+        position.lng = geo_util::ambiguity_centre(position.lng, ambiguity);
+
+        let sp: u16 = u16::from(data[4] - 28);
+        let dc: u16 = u16::from(data[5] - 28);
+        let se: u16 = u16::from(data[6] - 28);
+        let mut speed: u16 = sp * 10 + dc / 10;
+        if speed >= 800 {
+            speed -= 800;
+        }
+        let mut course: u16 = (dc % 10) * 100 + se;
+        if course >= 400 {
+            course -= 400;
+        }
+        position.speed = Some(f32::from(speed));
+        position.course = if course == 0 { None } else { Some(course) };
+        position.symbol = data[7];
+        position.table = match data[8] {
+            b'/' | b'\\' | b'0'..=b'9' | b'A'..=b'Z' => vec![data[8]],
+            _ => return Err(ParseError::Position { offset: 8 }),
+        };
+
+        // Status text: type code, altitude and comment
+        let mut text: &[u8] = &data[9..];
+        let mut radio: Option<&'static str> = None;
+        if let Some(lead) = text.first().cloned() {
+            for &(code, trailer, name) in KENWOOD_TYPES {
+                if lead != code {
+                    continue;
+                }
+                match trailer {
+                    Some(trailer) if text.len() > 1 && text.ends_with(&[trailer]) => {
+                        text = &text[1..(text.len() - 1)];
+                    },
+                    Some(_) => continue,
+                    None => text = &text[1..],
+                }
+                radio = Some(name);
+                break;
+            }
+            if lead == b'`' || lead == b'\'' {
+                text = &text[1..];
+                for &(code, suffix, name) in SUFFIX_TYPES {
+                    if lead == code && text.ends_with(suffix) {
+                        text = &text[..(text.len() - 2)];
+                        radio = Some(name);
+                        break;
+                    }
+                }
+            }
+        }
+        if text.len() >= 4 && text[3] == b'}' {
+            if let Some(alt) = base91::decode(&text[..3]) {
+                position.altitude = Some((alt as f32 - 10_000_f32) * 3.280_84_f32);
+                text = &text[4..];
+            }
+        }
+        position.comment = text.to_vec();
+        if let Some((telemetry, comment)) = telemetry::extract_compressed_telemetry(text) {
+            position.telemetry = Some(telemetry);
+            position.comment = comment;
+        }
+
+        Ok(MicE {
+            position,
+            message,
+            current,
+            radio,
+        })
+    }
+}
@@ -0,0 +1,269 @@
+//! Raw NMEA sentences
+//!
+//! Trackers without APRS formatting send GPS sentences as the information field, such as
+//! `$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A`. RMC, GGA, GLL and
+//! VTG sentences from any talker are decoded. A trailing `*HH` checksum, the XOR of every byte
+//! between `$` and `*`, is verified when present.
+//!
+//! Sentences with a position convert to a `PositionFrame`, so `PositionFrame::from_info_field`
+//! accepts them like any other position report.
+
+use std::str;
+
+use structs::{Frame, InformationField, PositionFrame};
+use errors::ParseError;
+use timestamp::Timestamp;
+
+
+const FEET_PER_METRE: f32 = 3.280_84;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SentenceType {
+    /// Recommended minimum data: time, date, position, speed and course
+    Rmc,
+    /// Fix data: time, position, fix quality and altitude
+    Gga,
+    /// Geographic position: position and time
+    Gll,
+    /// Track made good: course and speed
+    Vtg,
+}
+
+/// Fix quality reported by a GGA sentence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixQuality {
+    Invalid,
+    Gps,
+    Dgps,
+    Pps,
+    Rtk,
+    FloatRtk,
+    Estimated,
+    Manual,
+    Simulation,
+}
+
+impl FixQuality {
+    pub fn from_digit(digit: u8) -> Option<FixQuality> {
+        match digit {
+            0 => Some(FixQuality::Invalid),
+            1 => Some(FixQuality::Gps),
+            2 => Some(FixQuality::Dgps),
+            3 => Some(FixQuality::Pps),
+            4 => Some(FixQuality::Rtk),
+            5 => Some(FixQuality::FloatRtk),
+            6 => Some(FixQuality::Estimated),
+            7 => Some(FixQuality::Manual),
+            8 => Some(FixQuality::Simulation),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nmea {
+    /// Talker identifier, such as `GP` or `GN`
+    pub talker: String,
+    pub sentence: SentenceType,
+    /// UTC time of the fix as `HHMMSS`
+    pub time: Option<String>,
+    /// UTC date of the fix as `DDMMYY`, from RMC sentences
+    pub date: Option<String>,
+    pub lat: Option<f32>,
+    pub lng: Option<f32>,
+    /// `false` if the receiver flags the data as invalid
+    pub valid: bool,
+    /// Course over ground in degrees
+    pub course: Option<f32>,
+    /// Speed over ground in knots
+    pub speed: Option<f32>,
+    /// Altitude above mean sea level in metres
+    pub altitude: Option<f32>,
+    pub fix_quality: Option<FixQuality>,
+    pub satellites: Option<u8>,
+}
+
+impl Nmea {
+    /// Decode the NMEA sentence carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Nmea, ParseError> {
+        Nmea::from_info_field(&frame.info)
+    }
+
+    /// Decode a `$` information field into an NMEA sentence.
+    pub fn from_info_field(info: &InformationField) -> Result<Nmea, ParseError> {
+        parse_nmea(&info.data)
+    }
+
+    /// Convert to a position report without messaging, timestamped with the time of the fix.
+    ///
+    /// Returns `None` for sentences without a position.
+    pub fn to_position(&self) -> Option<PositionFrame> {
+        let mut position: PositionFrame = PositionFrame::new();
+        position.lat = self.lat?;
+        position.lng = self.lng?;
+        position.table = b"/".to_vec();
+        position.symbol = b'/';
+        position.messaging = false;
+        position.timestamp = self.time.as_ref()
+            .and_then(|time| Timestamp::parse(format!("{}h", time).as_bytes()).ok());
+        position.course = self.course.map(|course| course.round() as u16 % 360);
+        position.speed = self.speed;
+        position.altitude = self.altitude.map(|altitude| altitude * FEET_PER_METRE);
+        Some(position)
+    }
+}
+
+/// Parse a `$` NMEA sentence information field.
+pub fn parse_nmea(raw_data: &[u8]) -> Result<Nmea, ParseError> {
+    match raw_data.first() {
+        Some(&b'$') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    let data: &str = str::from_utf8(raw_data)
+        .map_err(|e| ParseError::InfoField { offset: e.valid_up_to() })?;
+    let data: &str = data.trim_end_matches(&['\r', '\n', ' '][..]);
+
+    let body_end: usize = match data.rfind('*') {
+        Some(star) => {
+            let checksum: u8 = u8::from_str_radix(&data[(star + 1)..], 16)
+                .map_err(|_| ParseError::InfoField { offset: star + 1 })?;
+            if data.len() != star + 3 {
+                return Err(ParseError::InfoField { offset: star + 1 });
+            }
+            if data[1..star].bytes().fold(0, |sum, byt| sum ^ byt) != checksum {
+                return Err(ParseError::InfoField { offset: star + 1 });
+            }
+            star
+        },
+        None => data.len(),
+    };
+
+    // (field, offset of the field)
+    let mut fields: Vec<(&str, usize)> = Vec::new();
+    let mut start: usize = 1;
+    for field in data[1..body_end].split(',') {
+        fields.push((field, start));
+        start += field.len() + 1;
+    }
+    let address: &str = fields[0].0;
+    if address.len() != 5 || !address.is_ascii() {
+        return Err(ParseError::InfoField { offset: 1 });
+    }
+    let sentence: SentenceType = match &address[2..] {
+        "RMC" => SentenceType::Rmc,
+        "GGA" => SentenceType::Gga,
+        "GLL" => SentenceType::Gll,
+        "VTG" => SentenceType::Vtg,
+        _ => return Err(ParseError::InfoField { offset: 3 }),
+    };
+    let mut nmea: Nmea = Nmea {
+        talker: address[..2].to_string(),
+        sentence,
+        time: None,
+        date: None,
+        lat: None,
+        lng: None,
+        valid: true,
+        course: None,
+        speed: None,
+        altitude: None,
+        fix_quality: None,
+        satellites: None,
+    };
+    let field = |ind: usize| -> (&str, usize) {
+        fields.get(ind).cloned().unwrap_or(("", data.len()))
+    };
+
+    match sentence {
+        SentenceType::Rmc => {
+            nmea.time = parse_time(field(1))?;
+            nmea.valid = field(2).0 == "A";
+            nmea.lat = parse_coordinate(field(3), field(4), 2)?;
+            nmea.lng = parse_coordinate(field(5), field(6), 3)?;
+            nmea.speed = parse_number(field(7))?;
+            nmea.course = parse_number(field(8))?;
+            let (date, offset): (&str, usize) = field(9);
+            if !date.is_empty() {
+                if date.len() != 6 || !date.bytes().all(|byt| byt.is_ascii_digit()) {
+                    return Err(ParseError::InfoField { offset });
+                }
+                nmea.date = Some(date.to_string());
+            }
+        },
+        SentenceType::Gga => {
+            nmea.time = parse_time(field(1))?;
+            nmea.lat = parse_coordinate(field(2), field(3), 2)?;
+            nmea.lng = parse_coordinate(field(4), field(5), 3)?;
+            let (quality, offset): (&str, usize) = field(6);
+            nmea.fix_quality = match quality {
+                "" => None,
+                _ => Some(quality.parse().ok().and_then(FixQuality::from_digit)
+                          .ok_or(ParseError::InfoField { offset })?),
+            };
+            nmea.valid = nmea.fix_quality.is_some_and(|quality| quality != FixQuality::Invalid);
+            nmea.satellites = parse_number(field(7))?.map(|sats: f32| sats as u8);
+            nmea.altitude = parse_number(field(9))?;
+        },
+        SentenceType::Gll => {
+            nmea.lat = parse_coordinate(field(1), field(2), 2)?;
+            nmea.lng = parse_coordinate(field(3), field(4), 3)?;
+            nmea.time = parse_time(field(5))?;
+            nmea.valid = field(6).0 != "V";
+        },
+        SentenceType::Vtg => {
+            // NMEA 2.3 sentences label each value; older ones are four bare numbers
+            if field(2).0 == "T" || field(4).0 == "M" {
+                nmea.course = parse_number(field(1))?;
+                nmea.speed = parse_number(field(5))?;
+            } else {
+                nmea.course = parse_number(field(1))?;
+                nmea.speed = parse_number(field(3))?;
+            }
+        },
+    }
+    Ok(nmea)
+}
+
+fn parse_number((field, offset): (&str, usize)) -> Result<Option<f32>, ParseError> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    field.parse().map(Some).map_err(|_| ParseError::InfoField { offset })
+}
+
+// `HHMMSS` with optional fractional seconds, which are dropped.
+fn parse_time((field, offset): (&str, usize)) -> Result<Option<String>, ParseError> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    let time: &str = field.split('.').next().unwrap_or("");
+    if time.len() != 6 || !time.bytes().all(|byt| byt.is_ascii_digit()) {
+        return Err(ParseError::InfoField { offset });
+    }
+    Ok(Some(time.to_string()))
+}
+
+// `DDMM.MMMM` or `DDDMM.MMMM` and a hemisphere letter.
+fn parse_coordinate((value, offset): (&str, usize), (hemisphere, hemi_offset): (&str, usize),
+                    degree_digits: usize) -> Result<Option<f32>, ParseError> {
+    if value.is_empty() && hemisphere.is_empty() {
+        return Ok(None);
+    }
+    if value.len() < degree_digits + 2 || !value.is_char_boundary(degree_digits) {
+        return Err(ParseError::InfoField { offset });
+    }
+    let degrees: f32 = value[..degree_digits].parse()
+        .map_err(|_| ParseError::InfoField { offset })?;
+    let minutes: f32 = value[degree_digits..].parse()
+        .map_err(|_| ParseError::InfoField { offset: offset + degree_digits })?;
+    if minutes >= 60.0 {
+        return Err(ParseError::InfoField { offset: offset + degree_digits });
+    }
+    let coordinate: f32 = degrees + minutes / 60.0;
+    match (hemisphere, degree_digits) {
+        ("N", 2) | ("E", 3) => Ok(Some(coordinate)),
+        ("S", 2) | ("W", 3) => Ok(Some(-coordinate)),
+        _ => Err(ParseError::InfoField { offset: hemi_offset }),
+    }
+}
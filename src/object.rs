@@ -0,0 +1,254 @@
+//! Object and item reports
+//!
+//! Objects and items describe something other than the sending station, such as an aid station
+//! or a checkpoint. An object is a `;`, a nine-character name padded with spaces, `*` if live or
+//! `_` if killed, a seven-character timestamp, a position and a comment. An item is a `)`, a
+//! three- to nine-character name, `!` if live or `_` if killed, a position and a comment.
+//!
+//! Positions may be uncompressed or compressed. They are held in a `PositionFrame`, whose
+//! address fields are left empty. Area objects and signposts keep their shape or sign text in
+//! its `area` and `signpost` fields, and repeater objects their frequency in `frequency`.
+
+use std::str;
+
+use structs::{Frame, InformationField, PositionFrame};
+use errors::{ParseError, EncodeError};
+use util::check_printable;
+use functions;
+use area;
+use frequency::{self, Frequency};
+use timestamp::Timestamp;
+
+
+/// Length of the padded object name field.
+pub const OBJECT_NAME_LEN: usize = 9;
+/// Minimum length of an item name.
+pub const ITEM_NAME_MIN: usize = 3;
+/// Maximum length of an item name.
+pub const ITEM_NAME_MAX: usize = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// Object name with the padding removed
+    pub name: String,
+    /// `false` once the object has been killed
+    pub live: bool,
+    pub timestamp: Timestamp,
+    pub position: PositionFrame,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    /// `false` once the item has been killed
+    pub live: bool,
+    pub position: PositionFrame,
+}
+
+impl Object {
+    pub fn new() -> Object {
+        Object {
+            name: String::new(),
+            live: true,
+            timestamp: Timestamp::DhmZulu { day: 0, hour: 0, minute: 0 },
+            position: PositionFrame::new(),
+        }
+    }
+
+    /// Repeater directory object for a repeater at `lat`, `lng`.
+    ///
+    /// The object is named after the frequency followed by `suffix`, up to two characters
+    /// telling apart repeaters on the same frequency, and has the repeater symbol.
+    pub fn repeater(frequency: Frequency, suffix: &str, lat: f32, lng: f32) -> Object {
+        let mut object: Object = Object::new();
+        object.name = frequency.object_name(suffix);
+        object.position.lat = lat;
+        object.position.lng = lng;
+        object.position.table = b"/".to_vec();
+        object.position.symbol = b'r';
+        object.position.frequency = Some(frequency);
+        object
+    }
+}
+
+impl Default for Object {
+    fn default() -> Object {
+        Object::new()
+    }
+}
+
+impl Object {
+    /// Decode the object carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Object, ParseError> {
+        Object::from_info_field(&frame.info)
+    }
+
+    /// Decode a `;` information field into an object.
+    pub fn from_info_field(info: &InformationField) -> Result<Object, ParseError> {
+        parse_object(&info.data)
+    }
+
+    /// Encode as a `;` information field.
+    ///
+    /// The name may be at most nine printable characters and the timestamp may not be `MMDDHHMM`.
+    /// The position needs a symbol table of `/`, `\`, a digit or a capital letter, and a printable
+    /// symbol. An area or signpost needs the matching symbol, and it and any frequency must fit
+    /// their codes unchanged.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        if self.name.is_empty() {
+            return Err(EncodeError::Missing { field: "object name" });
+        }
+        if self.name.len() > OBJECT_NAME_LEN {
+            return Err(EncodeError::TooLong { field: "object name", max: OBJECT_NAME_LEN });
+        }
+        check_printable(self.name.as_bytes(), b"", "object name")?;
+        if let Timestamp::Mdhm { .. } = self.timestamp {
+            return Err(EncodeError::TooLong { field: "timestamp", max: 7 });
+        }
+        check_symbol(&self.position)?;
+        area::check(&self.position)?;
+        frequency::check(&self.position)?;
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b';');
+        info.extend(format!("{:<9}", self.name).as_bytes());
+        info.push(if self.live { b'*' } else { b'_' });
+        info.extend(self.timestamp.to_string().as_bytes());
+        info.extend(self.position.create_body());
+
+        Ok(info)
+    }
+}
+
+impl Item {
+    pub fn new() -> Item {
+        Item {
+            name: String::new(),
+            live: true,
+            position: PositionFrame::new(),
+        }
+    }
+}
+
+impl Default for Item {
+    fn default() -> Item {
+        Item::new()
+    }
+}
+
+impl Item {
+    /// Decode the item carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Item, ParseError> {
+        Item::from_info_field(&frame.info)
+    }
+
+    /// Decode a `)` information field into an item.
+    pub fn from_info_field(info: &InformationField) -> Result<Item, ParseError> {
+        parse_item(&info.data)
+    }
+
+    /// Encode as a `)` information field.
+    ///
+    /// The name must be three to nine printable characters other than `!` and `_`. The position
+    /// needs a symbol table and symbol as for objects. An area or signpost needs the matching
+    /// symbol, and it and any frequency must fit their codes unchanged.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        if self.name.len() < ITEM_NAME_MIN {
+            return Err(EncodeError::TooShort { field: "item name", min: ITEM_NAME_MIN });
+        }
+        if self.name.len() > ITEM_NAME_MAX {
+            return Err(EncodeError::TooLong { field: "item name", max: ITEM_NAME_MAX });
+        }
+        check_printable(self.name.as_bytes(), b"!_", "item name")?;
+        check_symbol(&self.position)?;
+        area::check(&self.position)?;
+        frequency::check(&self.position)?;
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b')');
+        info.extend(self.name.as_bytes());
+        info.push(if self.live { b'!' } else { b'_' });
+        info.extend(self.position.create_body());
+
+        Ok(info)
+    }
+}
+
+/// Parse a `;` object information field.
+pub fn parse_object(raw_data: &[u8]) -> Result<Object, ParseError> {
+    match raw_data.first() {
+        Some(&b';') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    if raw_data.len() < OBJECT_NAME_LEN + 2 {
+        return Err(ParseError::InfoField { offset: raw_data.len() });
+    }
+    let name: &str = str::from_utf8(&raw_data[1..(OBJECT_NAME_LEN + 1)])
+        .map_err(|e| ParseError::InfoField { offset: 1 + e.valid_up_to() })?;
+    let live: bool = match raw_data[OBJECT_NAME_LEN + 1] {
+        b'*' => true,
+        b'_' => false,
+        _ => return Err(ParseError::InfoField { offset: OBJECT_NAME_LEN + 1 }),
+    };
+    let ts_start: usize = OBJECT_NAME_LEN + 2;
+    let timestamp: Timestamp = functions::parse_raw_timestamp(&raw_data[ts_start..])
+        .map_err(|e| e.shift(ts_start))?;
+    let body_start: usize = ts_start + 7;
+    let position: PositionFrame = functions::parse_position_body(&raw_data[body_start..])
+        .map_err(|e| e.shift(body_start))?;
+
+    Ok(Object {
+        name: name.trim_end().to_string(),
+        live,
+        timestamp,
+        position,
+    })
+}
+
+/// Parse a `)` item information field.
+pub fn parse_item(raw_data: &[u8]) -> Result<Item, ParseError> {
+    match raw_data.first() {
+        Some(&b')') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    // The name ends at the first live or killed flag
+    let flag: usize = match raw_data.iter().skip(1).take(ITEM_NAME_MAX + 1)
+        .position(|byt| *byt == b'!' || *byt == b'_') {
+        Some(pos) if pos >= ITEM_NAME_MIN => pos + 1,
+        Some(pos) => return Err(ParseError::InfoField { offset: pos + 1 }),
+        None => return Err(ParseError::InfoField { offset: raw_data.len().min(ITEM_NAME_MAX + 1) }),
+    };
+    let name: &str = str::from_utf8(&raw_data[1..flag])
+        .map_err(|e| ParseError::InfoField { offset: 1 + e.valid_up_to() })?;
+    let body_start: usize = flag + 1;
+    let position: PositionFrame = functions::parse_position_body(&raw_data[body_start..])
+        .map_err(|e| e.shift(body_start))?;
+
+    Ok(Item {
+        name: name.to_string(),
+        live: raw_data[flag] == b'!',
+        position,
+    })
+}
+
+fn check_symbol(position: &PositionFrame) -> Result<(), EncodeError> {
+    match position.table.first() {
+        None => return Err(EncodeError::Missing { field: "symbol table" }),
+        Some(&byt) if !is_symbol_table(byt) => {
+            return Err(EncodeError::InvalidCharacter { field: "symbol table", offset: 0 });
+        },
+        Some(_) if position.table.len() > 1 => {
+            return Err(EncodeError::InvalidCharacter { field: "symbol table", offset: 1 });
+        },
+        Some(_) => {},
+    }
+    match position.symbol {
+        0 => Err(EncodeError::Missing { field: "symbol" }),
+        byt if !byt.is_ascii_graphic() => Err(EncodeError::InvalidCharacter { field: "symbol", offset: 0 }),
+        _ => Ok(()),
+    }
+}
+
+fn is_symbol_table(byt: u8) -> bool {
+    byt == b'/' || byt == b'\\' || byt.is_ascii_digit() || byt.is_ascii_uppercase()
+}
@@ -0,0 +1,284 @@
+//! Object and item announcement
+//!
+//! The `ObjectManager` owns a set of objects and items and produces the frames that announce
+//! them. New or updated reports go out immediately and are repeated on a decaying schedule: each
+//! interval is twice the previous one, until the normal rate is reached. Removed reports are
+//! announced as killed a few times before they are forgotten, and reports that have not been
+//! updated within the configured lifetime are killed automatically.
+//!
+//! When another station transmits an object or item with one of our names it has taken it over,
+//! so the manager stops announcing it and reports the takeover as an event.
+//!
+//! Like the `Messenger`, the manager does no I/O. Call `poll` regularly, transmit the frames it
+//! returns, and feed it every received frame with `receive`.
+
+use std::time::Duration;
+
+use clock::Clock;
+use constants;
+use errors::EncodeError;
+use object::{Object, Item};
+use structs::{Frame, Callsign, InformationField};
+
+
+/// Object or item owned by the manager.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Report {
+    Object(Object),
+    Item(Item),
+}
+
+impl Report {
+    pub fn name(&self) -> &str {
+        match *self {
+            Report::Object(ref object) => &object.name,
+            Report::Item(ref item) => &item.name,
+        }
+    }
+    fn same_kind(&self, other: &Report) -> bool {
+        matches!((self, other), (&Report::Object(_), &Report::Object(_)) |
+                                (&Report::Item(_), &Report::Item(_)))
+    }
+    fn set_live(&mut self, live: bool) {
+        match *self {
+            Report::Object(ref mut object) => object.live = live,
+            Report::Item(ref mut item) => item.live = live,
+        }
+    }
+    fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        match *self {
+            Report::Object(ref object) => object.create_info_field(),
+            Report::Item(ref item) => item.create_info_field(),
+        }
+    }
+}
+
+/// Change to the set of managed reports caused by another station.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectEvent {
+    /// Another station transmitted one of our reports; it is no longer announced.
+    TakenOver { name: String, station: Callsign, live: bool },
+}
+
+struct Entry {
+    report: Report,
+    frame: Frame,
+    interval: Duration,
+    next_tx: Duration,
+    updated: Duration,
+    // Kill announcements still to send once the report has been removed
+    kills_left: Option<usize>,
+}
+
+pub struct ObjectManager<C: Clock> {
+    callsign: Callsign,
+    destination: Callsign,
+    path: Vec<Callsign>,
+    clock: C,
+    initial_interval: Duration,
+    max_interval: Duration,
+    kill_count: usize,
+    lifetime: Option<Duration>,
+    entries: Vec<Entry>,
+    events: Vec<ObjectEvent>,
+}
+
+impl<C: Clock> ObjectManager<C> {
+    /// Create a manager announcing reports from our callsign.
+    ///
+    /// Reports are sent to the default tocall with no path. They are repeated after 8 seconds,
+    /// doubling up to every 10 minutes, and killed reports are announced three times. Reports
+    /// never age out.
+    pub fn new(callsign: Callsign, clock: C) -> ObjectManager<C> {
+        let mut destination: Callsign = Callsign::new();
+        destination.set_callsign(constants::DEFAULT_TOCALL.to_string());
+        ObjectManager {
+            callsign,
+            destination,
+            path: Vec::new(),
+            clock,
+            initial_interval: Duration::from_secs(8),
+            max_interval: Duration::from_secs(10 * 60),
+            kill_count: 3,
+            lifetime: None,
+            entries: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl<C: Clock> ObjectManager<C> {
+    pub fn set_destination(&mut self, destination: Callsign) {
+        self.destination = destination;
+    }
+    pub fn set_path(&mut self, path: Vec<Callsign>) {
+        self.path = path;
+    }
+    /// Delay before the first repeat. Each following repeat waits twice as long as the last.
+    pub fn set_initial_interval(&mut self, interval: Duration) {
+        self.initial_interval = interval;
+    }
+    /// Normal repeat rate reached once the schedule has decayed.
+    pub fn set_max_interval(&mut self, interval: Duration) {
+        self.max_interval = interval;
+    }
+    /// Number of times a removed report is announced as killed.
+    pub fn set_kill_count(&mut self, count: usize) {
+        self.kill_count = count;
+    }
+    /// Kill reports that have not been updated for this long. `None` keeps them forever.
+    pub fn set_lifetime(&mut self, lifetime: Option<Duration>) {
+        self.lifetime = lifetime;
+    }
+
+    /// Live reports currently announced.
+    pub fn reports(&self) -> Vec<&Report> {
+        self.entries.iter()
+            .filter(|entry| entry.kills_left.is_none())
+            .map(|entry| &entry.report)
+            .collect()
+    }
+
+    /// Add or update an object. It is announced by the next call to `poll`.
+    ///
+    /// Adding an object that is still being announced as killed brings it back to life, and the
+    /// remaining kills are not sent.
+    pub fn add_object(&mut self, object: Object) -> Result<(), EncodeError> {
+        self.add(Report::Object(object))
+    }
+
+    /// Add or update an item. It is announced by the next call to `poll`.
+    ///
+    /// Adding an item that is still being announced as killed brings it back to life, and the
+    /// remaining kills are not sent.
+    pub fn add_item(&mut self, item: Item) -> Result<(), EncodeError> {
+        self.add(Report::Item(item))
+    }
+
+    /// Remove a report and announce it as killed. Returns `false` if no live report has the name.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let now: Duration = self.clock.now();
+        match self.entries.iter().position(|entry| entry.kills_left.is_none() &&
+                                            entry.report.name() == name) {
+            Some(pos) => {
+                self.kill(pos, now);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Return the frames due for transmission.
+    pub fn poll(&mut self) -> Vec<Frame> {
+        let now: Duration = self.clock.now();
+        let mut frames: Vec<Frame> = Vec::new();
+
+        if let Some(lifetime) = self.lifetime {
+            for pos in 0..self.entries.len() {
+                let entry: &Entry = &self.entries[pos];
+                if entry.kills_left.is_none() && now >= entry.updated.saturating_add(lifetime) {
+                    self.kill(pos, now);
+                }
+            }
+        }
+        for entry in self.entries.iter_mut() {
+            if entry.next_tx > now {
+                continue;
+            }
+            frames.push(entry.frame.clone());
+            if let Some(ref mut kills_left) = entry.kills_left {
+                *kills_left = kills_left.saturating_sub(1);
+            }
+            entry.next_tx = now.saturating_add(entry.interval);
+            entry.interval = entry.interval.checked_mul(2).unwrap_or(self.max_interval)
+                .min(self.max_interval);
+        }
+        self.entries.retain(|entry| entry.kills_left != Some(0));
+        frames
+    }
+
+    /// Check a received frame for another station transmitting one of our reports.
+    pub fn receive(&mut self, frame: &Frame) {
+        if frame.source == self.callsign {
+            return;
+        }
+        let (name, live, is_object): (String, bool, bool) =
+            if let Ok(object) = Object::from_frame(frame) {
+                (object.name, object.live, true)
+            } else if let Ok(item) = Item::from_frame(frame) {
+                (item.name, item.live, false)
+            } else {
+                return;
+            };
+        // Reports being killed keep sending their kills; objects and items are separate names
+        let before: usize = self.entries.len();
+        self.entries.retain(|entry| {
+            let same_kind: bool = match entry.report {
+                Report::Object(_) => is_object,
+                Report::Item(_) => !is_object,
+            };
+            !(same_kind && entry.kills_left.is_none() && entry.report.name() == name)
+        });
+        if self.entries.len() != before {
+            self.events.push(ObjectEvent::TakenOver {
+                name,
+                station: frame.source.clone(),
+                live,
+            });
+        }
+    }
+
+    /// Take the events produced since the last call.
+    pub fn events(&mut self) -> Vec<ObjectEvent> {
+        self.events.drain(..).collect()
+    }
+
+    fn add(&mut self, mut report: Report) -> Result<(), EncodeError> {
+        report.set_live(true);
+        let frame: Frame = self.create_frame(&report)?;
+        let now: Duration = self.clock.now();
+        let entry: Entry = Entry {
+            report,
+            frame,
+            interval: self.initial_interval,
+            next_tx: now,
+            updated: now,
+            kills_left: None,
+        };
+        // A live announcement supersedes any kills still pending for the same report
+        match self.entries.iter().position(|old| old.report.same_kind(&entry.report) &&
+                                           old.report.name() == entry.report.name()) {
+            Some(pos) => self.entries[pos] = entry,
+            None => self.entries.push(entry),
+        }
+        Ok(())
+    }
+
+    fn kill(&mut self, pos: usize, now: Duration) {
+        let initial_interval: Duration = self.initial_interval;
+        let kill_count: usize = self.kill_count;
+        let mut report: Report = self.entries[pos].report.clone();
+        report.set_live(false);
+        // The report was encoded successfully when added, so only the flag can change
+        if let Ok(frame) = self.create_frame(&report) {
+            let entry: &mut Entry = &mut self.entries[pos];
+            entry.report = report;
+            entry.frame = frame;
+            entry.interval = initial_interval;
+            entry.next_tx = now;
+            entry.kills_left = Some(kill_count.max(1));
+        }
+    }
+
+    fn create_frame(&self, report: &Report) -> Result<Frame, EncodeError> {
+        let mut info: InformationField = InformationField::new();
+        info.set_data(&report.create_info_field()?);
+        info.set_safe(true);
+        Ok(Frame {
+            source: self.callsign.clone(),
+            destination: self.destination.clone(),
+            path: self.path.clone(),
+            info,
+        })
+    }
+}
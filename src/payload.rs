@@ -0,0 +1,223 @@
+//! Data types and decoded payloads
+//!
+//! The first byte of an information field, the data type identifier, says what kind of packet
+//! it carries. `DataType` names every identifier of APRS 1.01 and 1.2; parsing an information
+//! field records it, and fields with an unassigned identifier are kept as `DataType::Unknown`
+//! instead of being rejected.
+//!
+//! `Payload` is the decoded packet, one variant per kind, so received frames can be handled with
+//! a single `match`. It is decoded from a whole frame because Mic-E positions keep part of their
+//! data in the destination address.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::functions;
+//! # use aprs::payload::{DataType, Payload};
+//! # fn main() {
+//! let frame = functions::parse_frame(b"N0CALL>APRS:>Net Control Center").unwrap();
+//! assert_eq!(frame.info.data_type, Some(DataType::Status));
+//! match Payload::from_frame(&frame).unwrap() {
+//!     Payload::Status(status) => assert_eq!(status.text, "Net Control Center"),
+//!     _ => unreachable!(),
+//! }
+//! # }
+//! ```
+
+use std::str;
+
+use structs::{Frame, PositionFrame};
+use errors::ParseError;
+use capabilities::{Capabilities, parse_capabilities};
+use message::Message;
+use mice::MicE;
+use nmea::Nmea;
+use object::{Object, Item};
+use query::{Query, parse_query};
+use status::Status;
+use telemetry::Telemetry;
+use third_party::ThirdParty;
+use weather::Weather;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+    /// `!`: position without timestamp, no messaging
+    PositionNoTimestamp,
+    /// `=`: position without timestamp, with messaging
+    PositionNoTimestampMessaging,
+    /// `/`: position with timestamp, no messaging
+    PositionTimestamp,
+    /// `@`: position with timestamp, with messaging
+    PositionTimestampMessaging,
+    /// `` ` `` or 0x1c: current Mic-E
+    MicE,
+    /// `'` or 0x1d: old Mic-E
+    OldMicE,
+    /// `:`: message, bulletin or announcement
+    Message,
+    /// `T`: telemetry report
+    Telemetry,
+    /// `;`: object
+    Object,
+    /// `)`: item
+    Item,
+    /// `>`: status
+    Status,
+    /// `_`: weather report without position
+    Weather,
+    /// `#` or `*`: Peet Bros raw weather data
+    PeetBros,
+    /// `$`: raw NMEA sentence
+    Nmea,
+    /// `$ULTW` or `!!`: Ultimeter raw weather data, in packet or logging mode
+    Ultimeter,
+    /// `}`: third-party traffic
+    ThirdParty,
+    /// `<`: station capabilities
+    Capabilities,
+    /// `?`: query
+    Query,
+    /// `%`: Agrelo DFJr / MicroFinder direction finding
+    Agrelo,
+    /// `+`: shelter data with time
+    Shelter,
+    /// `,`: invalid or test data
+    Test,
+    /// `.`: space weather
+    SpaceWeather,
+    /// `[`: Maidenhead grid locator beacon
+    Maidenhead,
+    /// `{`: user-defined format
+    UserDefined,
+    /// Any unassigned identifier
+    Unknown(u8),
+}
+
+impl DataType {
+    /// Data type of an information field from its first bytes.
+    ///
+    /// Returns `None` for an empty field.
+    pub fn from_data(data: &[u8]) -> Option<DataType> {
+        let data_type: DataType = match *data.first()? {
+            // `!` starts both positions and Ultimeter logging mode packets
+            b'!' if data.starts_with(b"!!") => DataType::Ultimeter,
+            b'!' => DataType::PositionNoTimestamp,
+            b'=' => DataType::PositionNoTimestampMessaging,
+            b'/' => DataType::PositionTimestamp,
+            b'@' => DataType::PositionTimestampMessaging,
+            b'`' | 0x1c => DataType::MicE,
+            b'\'' | 0x1d => DataType::OldMicE,
+            b':' => DataType::Message,
+            b'T' => DataType::Telemetry,
+            b';' => DataType::Object,
+            b')' => DataType::Item,
+            b'>' => DataType::Status,
+            b'_' => DataType::Weather,
+            b'#' | b'*' => DataType::PeetBros,
+            // `$` starts both NMEA sentences and Ultimeter packets
+            b'$' if data.starts_with(b"$ULTW") => DataType::Ultimeter,
+            b'$' => DataType::Nmea,
+            b'}' => DataType::ThirdParty,
+            b'<' => DataType::Capabilities,
+            b'?' => DataType::Query,
+            b'%' => DataType::Agrelo,
+            b'+' => DataType::Shelter,
+            b',' => DataType::Test,
+            b'.' => DataType::SpaceWeather,
+            b'[' => DataType::Maidenhead,
+            b'{' => DataType::UserDefined,
+            byt => DataType::Unknown(byt),
+        };
+        Some(data_type)
+    }
+}
+
+/// Decoded information field.
+///
+/// Kinds without a structure of their own keep the bytes after the data type identifier.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    /// `!`, `=`, `/` and `@` positions, including those with weather
+    Position(Box<PositionFrame>),
+    MicE(Box<MicE>),
+    Message(Message),
+    Telemetry(Telemetry),
+    Object(Box<Object>),
+    Item(Box<Item>),
+    Status(Status),
+    /// Positionless, Peet Bros and Ultimeter weather reports
+    Weather(Box<Weather>),
+    Nmea(Nmea),
+    ThirdParty(Box<ThirdParty>),
+    Capabilities(Capabilities),
+    Query(Query),
+    Agrelo(Vec<u8>),
+    Shelter(Vec<u8>),
+    Test(Vec<u8>),
+    SpaceWeather(Vec<u8>),
+    /// Grid square, as four or six characters, and comment of a `[` beacon
+    Maidenhead { grid: String, comment: Vec<u8> },
+    /// `{` packet of a user-defined format
+    UserDefined { user_id: u8, packet_type: u8, data: Vec<u8> },
+    /// Whole information field with an unassigned data type identifier
+    Unknown(Vec<u8>),
+}
+
+impl Payload {
+    /// Decode the information field of a received frame according to its data type.
+    pub fn from_frame(frame: &Frame) -> Result<Payload, ParseError> {
+        let data: &[u8] = &frame.info.data;
+        let rest: Vec<u8> = data.get(1..).unwrap_or_default().to_vec();
+        let data_type: DataType = frame.info.data_type.ok_or(ParseError::InfoField { offset: 0 })?;
+        let payload: Payload = match data_type {
+            DataType::PositionNoTimestamp |
+            DataType::PositionNoTimestampMessaging |
+            DataType::PositionTimestamp |
+            DataType::PositionTimestampMessaging => {
+                Payload::Position(Box::new(PositionFrame::from_frame(frame)?))
+            },
+            DataType::MicE | DataType::OldMicE => Payload::MicE(Box::new(MicE::from_frame(frame)?)),
+            DataType::Message => Payload::Message(Message::from_frame(frame)?),
+            DataType::Telemetry => Payload::Telemetry(Telemetry::from_frame(frame)?),
+            DataType::Object => Payload::Object(Box::new(Object::from_frame(frame)?)),
+            DataType::Item => Payload::Item(Box::new(Item::from_frame(frame)?)),
+            DataType::Status => Payload::Status(Status::from_frame(frame)?),
+            DataType::Weather | DataType::PeetBros | DataType::Ultimeter => {
+                Payload::Weather(Box::new(Weather::from_frame(frame)?))
+            },
+            DataType::Nmea => Payload::Nmea(Nmea::from_frame(frame)?),
+            DataType::ThirdParty => Payload::ThirdParty(Box::new(ThirdParty::from_frame(frame)?)),
+            DataType::Capabilities => Payload::Capabilities(parse_capabilities(data)?),
+            DataType::Query => Payload::Query(parse_query(data)?),
+            DataType::Agrelo => Payload::Agrelo(rest),
+            DataType::Shelter => Payload::Shelter(rest),
+            DataType::Test => Payload::Test(rest),
+            DataType::SpaceWeather => Payload::SpaceWeather(rest),
+            DataType::Maidenhead => parse_maidenhead(data)?,
+            DataType::UserDefined => {
+                if data.len() < 3 {
+                    return Err(ParseError::InfoField { offset: data.len() });
+                }
+                Payload::UserDefined { user_id: data[1], packet_type: data[2], data: data[3..].to_vec() }
+            },
+            DataType::Unknown(_) => Payload::Unknown(data.to_vec()),
+        };
+        Ok(payload)
+    }
+}
+
+// `[IO91SX]comment`
+fn parse_maidenhead(data: &[u8]) -> Result<Payload, ParseError> {
+    let end: usize = match data.iter().position(|byt| *byt == b']') {
+        Some(end) => end,
+        None => return Err(ParseError::InfoField { offset: data.len() }),
+    };
+    if end != 5 && end != 7 {
+        return Err(ParseError::InfoField { offset: end });
+    }
+    if let Some(pos) = data[1..end].iter().position(|byt| !byt.is_ascii_alphanumeric()) {
+        return Err(ParseError::InfoField { offset: pos + 1 });
+    }
+    let grid: &str = str::from_utf8(&data[1..end]).map_err(|_| ParseError::InfoField { offset: 1 })?;
+    Ok(Payload::Maidenhead { grid: grid.to_string(), comment: data[(end + 1)..].to_vec() })
+}
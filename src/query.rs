@@ -0,0 +1,159 @@
+//! Queries
+//!
+//! General queries are a `?` information field naming the query between question marks, such
+//! as `?APRS?`, `?IGATE?` or `?WX?`. Every station that supports the query answers, after a
+//! random delay so the replies do not collide. An optional target footprint, the latitude and
+//! longitude of a centre and a radius in miles, limits the query to the stations inside it:
+//! `?APRS? 34.02,-117.15,0200`.
+//!
+//! Directed queries are sent as messages to one station, with text such as `?APRSP` for its
+//! position, `?APRSS` for its status, `?APRSD` for the stations it hears directly and `?APRST`
+//! or `?PING?` for the path the query took to reach it. They are answered straight away.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::query::{QueryType, parse_query};
+//! # fn main() {
+//! let query = parse_query(b"?APRS? 34.02,-117.15,0200").unwrap();
+//! assert_eq!(query.query_type, QueryType::Aprs);
+//! assert!(query.footprint.unwrap().contains(34.1, -117.2));
+//! # }
+//! ```
+
+use std::str;
+
+use structs::{Frame, InformationField};
+use errors::ParseError;
+use geo_util;
+use message::{Message, MessageKind};
+
+
+const METRES_PER_MILE: f32 = 1609.344;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryType {
+    /// `?APRS?`: report position and status
+    Aprs,
+    /// `?IGATE?`: igates report their capabilities
+    Igate,
+    /// `?WX?`: weather stations report their weather
+    Weather,
+    /// `?APRSP`: report position
+    Position,
+    /// `?APRSS`: report status
+    Status,
+    /// `?APRSD`: list the stations heard directly
+    Heard,
+    /// `?APRST` or `?PING?`: report the path the query took
+    Ping,
+    /// Any other query, without its question marks
+    Other(String),
+}
+
+/// Area a general query is limited to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Footprint {
+    pub lat: f32,
+    pub lng: f32,
+    /// Radius in miles
+    pub radius: u16,
+}
+
+impl Footprint {
+    /// Whether a position lies within the footprint.
+    pub fn contains(&self, lat: f32, lng: f32) -> bool {
+        geo_util::distance(self.lat, self.lng, lat, lng) <= f32::from(self.radius) * METRES_PER_MILE
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub query_type: QueryType,
+    /// Target footprint of a general query
+    pub footprint: Option<Footprint>,
+}
+
+impl Query {
+    /// Decode the general query carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Query, ParseError> {
+        Query::from_info_field(&frame.info)
+    }
+
+    /// Decode a `?` information field into a general query.
+    pub fn from_info_field(info: &InformationField) -> Result<Query, ParseError> {
+        parse_query(&info.data)
+    }
+
+    /// Decode the directed query in a message, if its text is one.
+    pub fn from_message(message: &Message) -> Option<Query> {
+        if message.kind != MessageKind::Message || !message.text.starts_with('?') {
+            return None;
+        }
+        let text: String = message.text.trim().to_uppercase();
+        let query_type: QueryType = match text.as_str() {
+            "?APRSP" => QueryType::Position,
+            "?APRSS" => QueryType::Status,
+            "?APRSD" => QueryType::Heard,
+            "?APRST" => QueryType::Ping,
+            _ if text.len() > 2 && text.ends_with('?') => query_type(&text[1..(text.len() - 1)]),
+            _ => QueryType::Other(text[1..].to_string()),
+        };
+        Some(Query { query_type, footprint: None })
+    }
+}
+
+/// Parse a `?` general query information field.
+pub fn parse_query(raw_data: &[u8]) -> Result<Query, ParseError> {
+    match raw_data.first() {
+        Some(&b'?') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    let data: &str = str::from_utf8(raw_data)
+        .map_err(|e| ParseError::InfoField { offset: e.valid_up_to() })?;
+    let data: &str = data.trim_end_matches(&['\r', '\n', ' '][..]);
+    let end: usize = match data[1..].find('?') {
+        Some(pos) => pos + 1,
+        None => return Err(ParseError::InfoField { offset: data.len() }),
+    };
+    if end == 1 {
+        return Err(ParseError::InfoField { offset: 1 });
+    }
+    let footprint: Option<Footprint> = match data[(end + 1)..].trim_start() {
+        "" => None,
+        target => Some(parse_footprint(target, data.len() - target.len())?),
+    };
+    Ok(Query { query_type: query_type(&data[1..end]), footprint })
+}
+
+fn query_type(name: &str) -> QueryType {
+    match name {
+        "APRS" => QueryType::Aprs,
+        "IGATE" => QueryType::Igate,
+        "WX" => QueryType::Weather,
+        "PING" => QueryType::Ping,
+        _ => QueryType::Other(name.to_string()),
+    }
+}
+
+// `lat,lng,radius`, starting at `offset` in the information field.
+fn parse_footprint(target: &str, offset: usize) -> Result<Footprint, ParseError> {
+    let mut fields = target.split(',');
+    let mut field_offset: usize = offset;
+    let mut next_field = |min: f32, max: f32| -> Result<f32, ParseError> {
+        let field: &str = fields.next().unwrap_or("");
+        let value: f32 = match field.trim().parse::<f32>() {
+            Ok(value) if value >= min && value <= max => value,
+            _ => return Err(ParseError::InfoField { offset: field_offset }),
+        };
+        field_offset += field.len() + 1;
+        Ok(value)
+    };
+    let lat: f32 = next_field(-90.0, 90.0)?;
+    let lng: f32 = next_field(-180.0, 180.0)?;
+    let radius: f32 = next_field(0.0, f32::from(u16::MAX))?;
+    if fields.next().is_some() {
+        return Err(ParseError::InfoField { offset: field_offset - 1 });
+    }
+    Ok(Footprint { lat, lng, radius: radius as u16 })
+}
@@ -0,0 +1,258 @@
+//! Answering queries
+//!
+//! The `QueryResponder` answers the queries our station supports from its configuration: a
+//! position report, status text, the list of stations heard directly and, for igates, the number
+//! of messages gated.
+//!
+//! | Query               | Reply                                                   |
+//! |---------------------|---------------------------------------------------------|
+//! | `?APRS?`            | position report and status                              |
+//! | `?WX?`              | position report, if it carries weather                  |
+//! | `?IGATE?`           | `<IGATE,MSG_CNT=n,LOC_CNT=n` capabilities, for igates   |
+//! | `?APRSP`            | position report                                         |
+//! | `?APRSS`            | status                                                  |
+//! | `?APRSD`            | message listing the stations heard directly             |
+//! | `?APRST`, `?PING?`  | message with the path the query took                    |
+//!
+//! Replies to general queries are held for a random delay, up to two minutes by default, so the
+//! stations answering do not all transmit at once. Directed queries are answered straight away.
+//! General queries with a target footprint are only answered when our position lies inside it.
+//!
+//! Like the `Messenger`, the responder does no I/O. Feed it every received frame with `receive`,
+//! transmit the frames it returns, and call `poll` regularly for the delayed replies. Directed
+//! queries with a message number still need acknowledging, which the `Messenger` does.
+
+use std::time::Duration;
+
+use capabilities::Capabilities;
+use clock::Clock;
+use constants;
+use message::{self, Message, MessageKind};
+use payload::DataType;
+use query::{Query, QueryType};
+use random::Random;
+use status::Status;
+use structs::{Frame, Callsign, InformationField, PositionFrame};
+
+
+pub struct QueryResponder<C: Clock, R: Random> {
+    callsign: Callsign,
+    destination: Callsign,
+    path: Vec<Callsign>,
+    clock: C,
+    random: R,
+    max_delay: Duration,
+    position: Option<PositionFrame>,
+    status: Option<String>,
+    heard: Vec<Callsign>,
+    gated_messages: Option<u32>,
+    // (time due, reply)
+    pending: Vec<(Duration, Frame)>,
+}
+
+impl<C: Clock, R: Random> QueryResponder<C, R> {
+    /// Create a responder for our callsign, drawing reply delays from `random`.
+    ///
+    /// Replies are sent to the default tocall with no path, and nothing is reported until a
+    /// position or status is configured.
+    pub fn new(callsign: Callsign, clock: C, random: R) -> QueryResponder<C, R> {
+        let mut destination: Callsign = Callsign::new();
+        destination.set_callsign(constants::DEFAULT_TOCALL.to_string());
+        QueryResponder {
+            callsign,
+            destination,
+            path: Vec::new(),
+            clock,
+            random,
+            max_delay: Duration::from_secs(120),
+            position: None,
+            status: None,
+            heard: Vec::new(),
+            gated_messages: None,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<C: Clock, R: Random> QueryResponder<C, R> {
+    pub fn set_destination(&mut self, destination: Callsign) {
+        self.destination = destination;
+    }
+    pub fn set_path(&mut self, path: Vec<Callsign>) {
+        self.path = path;
+    }
+    /// Longest delay before answering a general query.
+    pub fn set_max_delay(&mut self, delay: Duration) {
+        self.max_delay = delay;
+    }
+    /// Position report sent in answer to `?APRS?`, `?APRSP` and, if it carries weather, `?WX?`.
+    pub fn set_position(&mut self, position: PositionFrame) {
+        self.position = Some(position);
+    }
+    /// Status text sent in answer to `?APRS?` and `?APRSS`.
+    pub fn set_status(&mut self, text: &str) {
+        self.status = Some(text.to_string());
+    }
+    /// Stations heard directly, listed in answer to `?APRSD`.
+    pub fn set_heard(&mut self, heard: Vec<Callsign>) {
+        self.heard = heard;
+    }
+    /// Number of messages gated to RF, reported in answer to `?IGATE?`. `None`, the default, means
+    /// the station is not an igate and ignores the query.
+    pub fn set_gated_messages(&mut self, count: Option<u32>) {
+        self.gated_messages = count;
+    }
+
+    /// Number of replies waiting for their delay to pass.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Return the delayed replies that are due for transmission.
+    pub fn poll(&mut self) -> Vec<Frame> {
+        let now: Duration = self.clock.now();
+        let mut due: Vec<(Duration, Frame)> = Vec::new();
+        let mut ind: usize = 0;
+        while ind < self.pending.len() {
+            if self.pending[ind].0 <= now {
+                due.push(self.pending.remove(ind));
+            } else {
+                ind += 1;
+            }
+        }
+        due.sort_by_key(|&(time, _)| time);
+        due.into_iter().map(|(_, frame)| frame).collect()
+    }
+
+    /// Process a received frame and return the replies to a directed query.
+    ///
+    /// Replies to general queries are queued for `poll`. Frames that are not queries for us are
+    /// ignored.
+    pub fn receive(&mut self, frame: &Frame) -> Vec<Frame> {
+        if frame.source.callsign.eq_ignore_ascii_case(&self.callsign.callsign) &&
+            frame.source.ssid == self.callsign.ssid {
+            return Vec::new();
+        }
+        match frame.info.data_type {
+            Some(DataType::Query) => {
+                let query: Query = match Query::from_frame(frame) {
+                    Ok(query) => query,
+                    Err(_) => return Vec::new(),
+                };
+                if let Some(footprint) = query.footprint {
+                    match self.position {
+                        Some(ref position) if footprint.contains(position.lat, position.lng) => {},
+                        _ => return Vec::new(),
+                    }
+                }
+                let replies: Vec<Frame> = match query.query_type {
+                    QueryType::Aprs | QueryType::Igate | QueryType::Weather => {
+                        self.replies(&query.query_type, frame)
+                    },
+                    _ => Vec::new(),
+                };
+                let due: Duration = self.clock.now().saturating_add(self.delay());
+                for reply in replies {
+                    // A reply still waiting answers repeated queries too
+                    if !self.pending.iter().any(|(_, pending)| *pending == reply) {
+                        self.pending.push((due, reply));
+                    }
+                }
+                Vec::new()
+            },
+            Some(DataType::Message) => {
+                let message: Message = match Message::from_frame(frame) {
+                    Ok(message) => message,
+                    Err(_) => return Vec::new(),
+                };
+                if !message.addressee.eq_ignore_ascii_case(&self.callsign.to_string()) {
+                    return Vec::new();
+                }
+                match Query::from_message(&message) {
+                    Some(query) => self.replies(&query.query_type, frame),
+                    None => Vec::new(),
+                }
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn replies(&self, query_type: &QueryType, query: &Frame) -> Vec<Frame> {
+        let position: Option<Frame> = self.position.as_ref()
+            .map(|position| self.create_frame(position.create_info_field()));
+        let status: Option<Frame> = self.status.as_ref().and_then(|text| {
+            let mut status: Status = Status::new();
+            status.text = text.clone();
+            status.create_info_field().ok().map(|info| self.create_frame(info))
+        });
+
+        match *query_type {
+            QueryType::Aprs => position.into_iter().chain(status).collect(),
+            QueryType::Weather => {
+                let has_weather: bool = self.position.as_ref()
+                    .is_some_and(|position| position.weather.is_some());
+                if has_weather { position.into_iter().collect() } else { Vec::new() }
+            },
+            QueryType::Igate => {
+                let capabilities: Option<Capabilities> = self.gated_messages
+                    .map(|count| Capabilities::igate(count, self.heard.len() as u32));
+                capabilities.and_then(|capabilities| capabilities.create_info_field().ok())
+                    .map(|info| self.create_frame(info))
+                    .into_iter().collect()
+            },
+            QueryType::Position => position.into_iter().collect(),
+            QueryType::Status => status.into_iter().collect(),
+            QueryType::Heard => {
+                let mut text: String = String::from("Directs=");
+                for station in self.heard.iter() {
+                    let station: String = station.to_string();
+                    if text.len() + station.len() + 1 > message::MAX_TEXT_LEN {
+                        break;
+                    }
+                    text.push(' ');
+                    text.push_str(&station);
+                }
+                self.create_message(&query.source, text).into_iter().collect()
+            },
+            QueryType::Ping => {
+                let mut route: Vec<u8> = query.encode_text();
+                route.truncate(route.len() - query.info.data.len() - 1);
+                route.truncate(message::MAX_TEXT_LEN);
+                self.create_message(&query.source, String::from_utf8_lossy(&route).into_owned())
+                    .into_iter().collect()
+            },
+            QueryType::Other(_) => Vec::new(),
+        }
+    }
+
+    fn delay(&mut self) -> Duration {
+        let max: u64 = self.max_delay.as_millis().min(u128::from(u64::MAX)) as u64;
+        if max == 0 {
+            return Duration::from_secs(0);
+        }
+        let millis: u64 = self.random.next_u64();
+        Duration::from_millis(max.checked_add(1).map_or(millis, |range| millis % range))
+    }
+
+    fn create_message(&self, addressee: &Callsign, text: String) -> Option<Frame> {
+        let message: Message = Message {
+            addressee: addressee.to_string(),
+            kind: MessageKind::Message,
+            text,
+            ..Message::new()
+        };
+        message.create_info_field().ok().map(|info| self.create_frame(info))
+    }
+
+    fn create_frame(&self, data: Vec<u8>) -> Frame {
+        let mut info: InformationField = InformationField::new();
+        info.set_data(&data);
+        info.set_safe(true);
+        Frame {
+            source: self.callsign.clone(),
+            destination: self.destination.clone(),
+            path: self.path.clone(),
+            info,
+        }
+    }
+}
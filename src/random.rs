@@ -0,0 +1,52 @@
+//! Random numbers
+//!
+//! Components that add jitter to their transmissions, such as the query responder, draw it
+//! through the `Random` trait so tests can supply a fixed sequence. `XorShift` is a small
+//! generator that is good enough to spread out replies; it is not suitable for anything security
+//! related.
+
+pub trait Random {
+    /// Next value, uniformly distributed over the whole range.
+    fn next_u64(&mut self) -> u64;
+}
+
+impl<R: Random> Random for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// xorshift64* generator.
+///
+/// ```rust
+/// # extern crate aprs;
+/// # use aprs::random::{Random, XorShift};
+/// # fn main() {
+/// let mut first = XorShift::new(42);
+/// let mut second = XorShift::new(42);
+/// assert_eq!(first.next_u64(), second.next_u64());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    /// Seed the generator, for instance with the current time. A zero seed is replaced, since the
+    /// generator would only ever return zero.
+    pub fn new(seed: u64) -> XorShift {
+        XorShift {
+            state: if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed }
+        }
+    }
+}
+
+impl Random for XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
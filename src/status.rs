@@ -0,0 +1,211 @@
+//! Status reports
+//!
+//! A status report is a `>` followed by free text. The text may be preceded by a `DDHHMMz`
+//! timestamp, or instead by a four- or six-character Maidenhead locator and a symbol, as in
+//! `>IO91SX/G Net control`. A status ending in `^` and two characters gives the heading and
+//! effective radiated power of a beam antenna.
+
+use std::str;
+
+use structs::{Frame, InformationField};
+use errors::{ParseError, EncodeError};
+use timestamp::Timestamp;
+use util::check_printable;
+
+
+/// Maximum length of everything after the `>`.
+pub const MAX_STATUS_LEN: usize = 62;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locator {
+    /// Four- or six-character Maidenhead grid square, such as `IO91SX`
+    pub grid: String,
+    pub table: u8,
+    pub symbol: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Status {
+    /// `DDHHMMz` timestamp; never present together with a locator
+    pub timestamp: Option<Timestamp>,
+    pub locator: Option<Locator>,
+    /// Beam heading in degrees, a multiple of 10
+    pub beam_heading: Option<u16>,
+    /// Effective radiated power of the beam in watts
+    pub erp: Option<u32>,
+    /// Status text without the timestamp, locator or beam suffix
+    pub text: String,
+}
+
+impl Status {
+    pub fn new() -> Status {
+        Status {
+            timestamp: None,
+            locator: None,
+            beam_heading: None,
+            erp: None,
+            text: String::new(),
+        }
+    }
+}
+
+impl Default for Status {
+    fn default() -> Status {
+        Status::new()
+    }
+}
+
+impl Status {
+    /// Decode the status carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Status, ParseError> {
+        Status::from_info_field(&frame.info)
+    }
+
+    /// Decode a `>` information field into a status.
+    pub fn from_info_field(info: &InformationField) -> Result<Status, ParseError> {
+        parse_status(&info.data)
+    }
+
+    /// Encode as a `>` information field.
+    ///
+    /// The timestamp must be in `DDHHMMz` form and the text may not contain `|` or `~`. Beam
+    /// heading and ERP must be set together; the heading is rounded to 10 degrees and the power
+    /// to the nearest value the code can represent, up to 12250 watts.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b'>');
+
+        match (self.timestamp.as_ref(), self.locator.as_ref()) {
+            (Some(_), Some(_)) => {
+                return Err(EncodeError::Conflict { field: "timestamp", other: "locator" });
+            },
+            (Some(timestamp), None) => {
+                match *timestamp {
+                    Timestamp::DhmZulu { .. } => info.extend(timestamp.to_string().as_bytes()),
+                    _ => return Err(EncodeError::InvalidCharacter { field: "timestamp", offset: 6 }),
+                }
+            },
+            (None, Some(locator)) => {
+                if !is_grid(locator.grid.as_bytes()) {
+                    return Err(EncodeError::InvalidCharacter { field: "locator", offset: 0 });
+                }
+                info.extend(locator.grid.to_uppercase().as_bytes());
+                info.push(locator.table);
+                info.push(locator.symbol);
+                if !self.text.is_empty() {
+                    info.push(b' ');
+                }
+            },
+            (None, None) => {},
+        }
+
+        check_printable(self.text.as_bytes(), b"|~", "text")?;
+        info.extend(self.text.as_bytes());
+
+        match (self.beam_heading, self.erp) {
+            (Some(heading), Some(erp)) => {
+                if heading > 359 {
+                    return Err(EncodeError::OutOfRange { field: "beam heading" });
+                }
+                let power: u32 = (erp as f32 / 10.0).sqrt().round() as u32;
+                if power > 35 {
+                    return Err(EncodeError::OutOfRange { field: "ERP" });
+                }
+                info.push(b'^');
+                info.push(to_code(((u32::from(heading) + 5) / 10 % 36) as u8));
+                info.push(to_code(power as u8));
+            },
+            (Some(_), None) => return Err(EncodeError::Missing { field: "ERP" }),
+            (None, Some(_)) => return Err(EncodeError::Missing { field: "beam heading" }),
+            (None, None) => {},
+        }
+
+        if info.len() - 1 > MAX_STATUS_LEN {
+            let max: usize = MAX_STATUS_LEN - (info.len() - 1 - self.text.len());
+            return Err(EncodeError::TooLong { field: "text", max });
+        }
+        Ok(info)
+    }
+}
+
+/// Parse a `>` status information field.
+pub fn parse_status(raw_data: &[u8]) -> Result<Status, ParseError> {
+    match raw_data.first() {
+        Some(&b'>') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    let mut status: Status = Status::new();
+    let mut text_start: usize = 1;
+    let body: &[u8] = &raw_data[1..];
+
+    if let Ok(timestamp @ Timestamp::DhmZulu { .. }) = Timestamp::parse(body) {
+        status.timestamp = Some(timestamp);
+        text_start += 7;
+    } else if let Some(len) = parse_locator(body) {
+        status.locator = Some(Locator {
+            grid: String::from_utf8_lossy(&body[..len]).into_owned(),
+            table: body[len],
+            symbol: body[len + 1],
+        });
+        text_start += len + 2;
+        if raw_data.get(text_start) == Some(&b' ') {
+            text_start += 1;
+        }
+    }
+
+    let mut text: &[u8] = &raw_data[text_start..];
+    if text.len() >= 3 && text[text.len() - 3] == b'^' {
+        let beam: &[u8] = &text[(text.len() - 2)..];
+        if let (Some(heading), Some(power)) = (from_code(beam[0]), from_code(beam[1])) {
+            status.beam_heading = Some(u16::from(heading) * 10);
+            status.erp = Some(u32::from(power) * u32::from(power) * 10);
+            text = &text[..(text.len() - 3)];
+        }
+    }
+    status.text = str::from_utf8(text)
+        .map_err(|e| ParseError::InfoField { offset: text_start + e.valid_up_to() })?
+        .to_string();
+    Ok(status)
+}
+
+// Length of the grid square when the status starts with a locator and symbol.
+fn parse_locator(body: &[u8]) -> Option<usize> {
+    for &len in &[6, 4] {
+        if body.len() < len + 2 || !is_grid(&body[..len]) {
+            continue;
+        }
+        let table: u8 = body[len];
+        let symbol_ok: bool = (0x21..0x7f).contains(&body[len + 1]);
+        let table_ok: bool = table == b'/' || table == b'\\' || table.is_ascii_digit() ||
+            table.is_ascii_uppercase();
+        let ends: bool = body.len() == len + 2 || body[len + 2] == b' ' || body[len + 2] == b'^';
+        if table_ok && symbol_ok && ends {
+            return Some(len);
+        }
+    }
+    None
+}
+
+fn is_grid(grid: &[u8]) -> bool {
+    let field = |byt: u8| (b'A'..=b'R').contains(&byt.to_ascii_uppercase());
+    let subsquare = |byt: u8| (b'A'..=b'X').contains(&byt.to_ascii_uppercase());
+    match grid.len() {
+        4 => field(grid[0]) && field(grid[1]) && grid[2].is_ascii_digit() && grid[3].is_ascii_digit(),
+        6 => is_grid(&grid[..4]) && subsquare(grid[4]) && subsquare(grid[5]),
+        _ => false,
+    }
+}
+
+// Beam heading and power codes are 0-9 followed by A-Z.
+fn from_code(byt: u8) -> Option<u8> {
+    match byt {
+        b'0'..=b'9' => Some(byt - b'0'),
+        b'A'..=b'Z' => Some(byt - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn to_code(value: u8) -> u8 {
+    if value < 10 { b'0' + value } else { b'A' + value - 10 }
+}
@@ -3,12 +3,28 @@
 //! Data is broken down into objects depending on its purpose. See each struct definition for its
 //! description.
 
+use std::fmt;
+
 use constants;
 use functions;
 use geo_util;
+use base91;
+use errors::ParseError;
 use fcs::FCS;
+use telemetry::CompressedTelemetry;
+use timestamp::Timestamp;
+use weather;
+use nmea;
+use payload::DataType;
+use weather::Weather;
+use extension;
+use extension::{Phg, Dfs, Dao};
+use area;
+use area::Area;
+use frequency::Frequency;
 
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Frame {
     pub source: Callsign,
     pub destination: Callsign,
@@ -27,21 +43,47 @@ impl Frame {
     }
 }
 
+impl Default for Frame {
+    fn default() -> Frame {
+        Frame::new()
+    }
+}
+
 impl Frame {
-    pub fn set_source(&mut self, source: &[u8]) {
-        self.source = functions::parse_callsign(source);
+    pub fn set_source(&mut self, source: &[u8]) -> Result<(), ParseError> {
+        self.source = functions::parse_callsign(source)?;
+        Ok(())
     }
-    pub fn set_destination(&mut self, dest: &[u8]) {
-        self.destination = functions::parse_callsign(dest);
+    pub fn set_destination(&mut self, dest: &[u8]) -> Result<(), ParseError> {
+        self.destination = functions::parse_callsign(dest)?;
+        Ok(())
     }
-    pub fn set_path(&mut self, path: Vec<Vec<u8>>) {
+    pub fn set_path(&mut self, path: Vec<Vec<u8>>) -> Result<(), ParseError> {
         for pth in path {
             let byts: &[u8] = pth.as_slice();
-            self.path.push(functions::parse_callsign(byts));
+            self.path.push(functions::parse_callsign(byts).map_err(|e| e.in_path())?);
         }
+        Ok(())
     }
-    pub fn set_info(&mut self, info: &[u8]) {
-        self.info = functions::parse_info_field(info);
+    pub fn set_info(&mut self, info: &[u8]) -> Result<(), ParseError> {
+        self.info = functions::parse_info_field(info)?;
+        Ok(())
+    }
+    /// Encode in TNC2 text form, `SOURCE>DEST,PATH:info`, as used on APRS-IS and in third-party
+    /// headers.
+    pub fn encode_text(&self) -> Vec<u8> {
+        let mut encoded_frame: Vec<u8> = Vec::new();
+        encoded_frame.extend(self.source.encode_text().as_bytes());
+        encoded_frame.push(b'>');
+        encoded_frame.extend(self.destination.encode_text().as_bytes());
+        for path_call in self.path.iter() {
+            encoded_frame.push(b',');
+            encoded_frame.extend(path_call.encode_text().as_bytes());
+        }
+        encoded_frame.push(b':');
+        encoded_frame.extend(self.info.data.as_slice());
+
+        encoded_frame
     }
     pub fn encode_ax25(&self) -> Vec<u8> {
         let mut encoded_frame: Vec<u8> = Vec::new();
@@ -51,10 +93,17 @@ impl Frame {
         for path_call in self.path.iter() {
             encoded_frame.extend(path_call.encode_ax25());
         }
+        // The low bit of the last address byte marks the end of the address field
+        if let Some(last) = encoded_frame.last_mut() {
+            *last |= 0x01;
+        }
+        encoded_frame.push(constants::AX25_CONTROL_FIELD);
+        encoded_frame.push(constants::AX25_PROTOCOL_ID);
         encoded_frame.extend(self.info.data.as_slice());
 
+        // The FCS covers everything between the flags
         let mut fcs: FCS = FCS::new();
-        fcs.update_bytes(&encoded_frame);
+        fcs.update_bytes(&encoded_frame[1..]);
 
         encoded_frame.extend(fcs.digest());
         encoded_frame.push(constants::AX25_FLAG);
@@ -63,6 +112,7 @@ impl Frame {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct PositionFrame {
     pub source: Callsign,
     pub destination: Callsign,
@@ -73,7 +123,35 @@ pub struct PositionFrame {
     pub symbol: u8,
     pub lat: f32,
     pub lng: f32,
-    pub ambiguity: usize
+    pub ambiguity: usize,
+    pub timestamp: Option<Timestamp>,
+    pub messaging: bool,
+    pub compressed: bool,
+    pub compression_type: Option<CompressionType>,
+    /// Course in degrees
+    pub course: Option<u16>,
+    /// Speed in knots
+    pub speed: Option<f32>,
+    /// Pre-calculated radio range in miles
+    pub range: Option<f32>,
+    /// Altitude in feet
+    pub altitude: Option<f32>,
+    /// Power, height, gain and directivity data extension
+    pub phg: Option<Phg>,
+    /// Direction-finding signal strength data extension
+    pub dfs: Option<Dfs>,
+    /// Form of the `!DAO!` precision extension, sent when the position is not ambiguous
+    pub dao: Option<Dao>,
+    /// Shape drawn by an area object, removed from the comment
+    pub area: Option<Area>,
+    /// Text shown by a signpost object, removed from the comment
+    pub signpost: Option<String>,
+    /// Voice frequency specification, removed from the comment
+    pub frequency: Option<Frequency>,
+    /// Compressed telemetry block, removed from the comment
+    pub telemetry: Option<CompressedTelemetry>,
+    /// Weather fields of a report with the weather symbol, removed from the comment
+    pub weather: Option<Weather>
 }
 
 impl PositionFrame {
@@ -88,45 +166,314 @@ impl PositionFrame {
             symbol: 0,
             lat: 0.0,
             lng: 0.0,
-            ambiguity: 0
+            ambiguity: 0,
+            timestamp: None,
+            messaging: true,
+            compressed: false,
+            compression_type: None,
+            course: None,
+            speed: None,
+            range: None,
+            altitude: None,
+            phg: None,
+            dfs: None,
+            dao: None,
+            area: None,
+            signpost: None,
+            frequency: None,
+            telemetry: None,
+            weather: None
         }
     }
 }
 
+impl Default for PositionFrame {
+    fn default() -> PositionFrame {
+        PositionFrame::new()
+    }
+}
+
 impl PositionFrame {
-    pub fn set_source(&mut self, source: &[u8]) {
-        self.source = functions::parse_callsign(source);
+    /// Decode the position report carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<PositionFrame, ParseError> {
+        let mut position: PositionFrame = PositionFrame::from_info_field(&frame.info)?;
+        position.source = frame.source.clone();
+        position.destination = frame.destination.clone();
+        position.path = frame.path.clone();
+        Ok(position)
+    }
+    /// Decode a `!`, `=`, `/` or `@` information field, or a raw NMEA sentence with a position,
+    /// into a position.
+    pub fn from_info_field(info: &InformationField) -> Result<PositionFrame, ParseError> {
+        let mut position: PositionFrame = if info.data.first() == Some(&b'$') {
+            nmea::parse_nmea(&info.data)?.to_position()
+                .ok_or(ParseError::InfoField { offset: info.data.len() })?
+        } else {
+            functions::parse_position(&info.data)?
+        };
+        position.info = info.clone();
+        Ok(position)
+    }
+    /// Overlay character printed over the symbol, if the table byte is not a primary or alternate
+    /// table identifier.
+    pub fn overlay(&self) -> Option<u8> {
+        match self.table.first() {
+            Some(&b'/') | Some(&b'\\') | None => None,
+            Some(byt) => Some(*byt),
+        }
+    }
+    /// Distance in metres from the decoded position to the corners of the area it stands for, or
+    /// `None` if the position is not ambiguous.
+    pub fn ambiguity_radius(&self) -> Option<f32> {
+        match self.ambiguity {
+            0 => None,
+            ambiguity => Some(geo_util::ambiguity_radius(self.lat, ambiguity)),
+        }
+    }
+    pub fn set_source(&mut self, source: &[u8]) -> Result<(), ParseError> {
+        self.source = functions::parse_callsign(source)?;
+        Ok(())
     }
-    pub fn set_destination(&mut self, dest: &[u8]) {
-        self.destination = functions::parse_callsign(dest);
+    pub fn set_destination(&mut self, dest: &[u8]) -> Result<(), ParseError> {
+        self.destination = functions::parse_callsign(dest)?;
+        Ok(())
     }
-    pub fn set_path(&mut self, path: Vec<Vec<u8>>) {
+    pub fn set_path(&mut self, path: Vec<Vec<u8>>) -> Result<(), ParseError> {
         for pth in path {
             let byts: &[u8] = pth.as_slice();
-            self.path.push(functions::parse_callsign(byts));
+            self.path.push(functions::parse_callsign(byts).map_err(|e| e.in_path())?);
         }
+        Ok(())
     }
-    pub fn update_info(&mut self) {
-        self.info = functions::parse_info_field(&self.create_info_field());
+    pub fn update_info(&mut self) -> Result<(), ParseError> {
+        self.info = functions::parse_info_field(&self.create_info_field())?;
+        Ok(())
     }
     pub fn create_info_field(&self) -> Vec<u8> {
-        let lat     = geo_util::dec2dm_lat(self.lat);
-        let lat_enc = geo_util::ambiguate(&lat, self.ambiguity);
-        let lng     = geo_util::dec2dm_lng(self.lng);
-        let lng_enc = geo_util::ambiguate(&lng, self.ambiguity);
-
         let mut frame: Vec<u8> = Vec::new();
-        frame.push(b'=');
-        frame.extend(lat_enc.as_bytes());
-        frame.extend(&self.table);
-        frame.extend(lng_enc.as_bytes());
-        frame.push(self.symbol);
-        frame.extend(&self.comment);
+        match (&self.timestamp, self.messaging) {
+            (&None, false) => frame.push(b'!'),
+            (&None, true) => frame.push(b'='),
+            (&Some(_), false) => frame.push(b'/'),
+            (&Some(_), true) => frame.push(b'@'),
+        }
+        match self.timestamp {
+            // Position reports have no room for the month
+            Some(Timestamp::Mdhm { day, hour, minute, .. }) => {
+                frame.extend(Timestamp::DhmZulu { day, hour, minute }.to_string().as_bytes());
+            },
+            Some(timestamp) => frame.extend(timestamp.to_string().as_bytes()),
+            None => {},
+        }
+        frame.extend(self.create_body());
 
         frame
     }
+    /// Encode the position and comment without the data type identifier and timestamp, as
+    /// embedded in objects and items. Weather fields or a data extension precede the comment,
+    /// followed by the `/A=` altitude when it is not carried by a compressed position. The `!DAO!`
+    /// extension and compressed telemetry follow it.
+    pub fn create_body(&self) -> Vec<u8> {
+        let mut body: Vec<u8> = if self.compressed {
+            self.create_compressed_body()
+        } else {
+            self.create_uncompressed_body()
+        };
+        match (self.area, self.weather.as_ref()) {
+            (Some(area), _) => body.extend(area.encode()),
+            (None, Some(weather)) => body.extend(weather.create_position_fields(self.compressed)),
+            (None, None) if !self.compressed => body.extend(extension::create_extension(self)),
+            (None, None) => {},
+        }
+        if let Some(ref frequency) = self.frequency {
+            body.extend(frequency.encode());
+            if self.altitude.is_some() || self.signpost.is_some() || !self.comment.is_empty() {
+                body.push(b' ');
+            }
+        }
+        if let Some(altitude) = self.altitude {
+            if !self.compressed || self.compressed_course_speed().is_some() || self.range.is_some() {
+                body.extend(extension::create_altitude(altitude));
+            }
+        }
+        if let Some(ref text) = self.signpost {
+            body.extend(area::create_signpost(text));
+        }
+        body.extend(&self.comment);
+        if let Some(dao) = self.uncompressed_dao() {
+            body.extend(extension::create_dao(self.lat, self.lng, dao).2);
+        }
+        if let Some(ref telemetry) = self.telemetry {
+            body.extend(telemetry.encode());
+        }
+
+        body
+    }
+    fn create_uncompressed_body(&self) -> Vec<u8> {
+        let (lat_enc, lng_enc): (String, String) = match self.uncompressed_dao() {
+            Some(dao) => {
+                let (lat, lng, _) = extension::create_dao(self.lat, self.lng, dao);
+                (lat, lng)
+            },
+            None => {
+                let lat = geo_util::dec2dm_lat(self.lat);
+                let lng = geo_util::dec2dm_lng(self.lng);
+                (geo_util::ambiguate(&lat, self.ambiguity), geo_util::ambiguate(&lng, self.ambiguity))
+            },
+        };
+
+        let mut body: Vec<u8> = Vec::new();
+        body.extend(lat_enc.as_bytes());
+        body.extend(&self.table);
+        body.extend(lng_enc.as_bytes());
+        body.push(self.symbol);
+
+        body
+    }
+    fn create_compressed_body(&self) -> Vec<u8> {
+        let mut body: Vec<u8> = Vec::new();
+        // Numeric overlays are sent as a-j so they cannot be mistaken for uncompressed latitude
+        match self.table.first() {
+            Some(byt) if byt.is_ascii_digit() => body.push(byt - b'0' + b'a'),
+            Some(byt) => body.push(*byt),
+            None => body.push(b'/'),
+        }
+        body.extend(geo_util::compress_lat(self.lat));
+        body.extend(geo_util::compress_lng(self.lng));
+        body.push(self.symbol);
+
+        let mut comp_type: CompressionType = self.compression_type.unwrap_or_default();
+        let cs: Option<Vec<u8>> = match (self.compressed_course_speed(), self.range, self.altitude) {
+            (Some((course, speed)), _, _) => {
+                let c: u8 = ((course % 360) / 4) as u8;
+                let s: f32 = ((speed + 1_f32).ln() / 1.08_f32.ln()).round().clamp(0_f32, 90_f32);
+                Some(vec![c + base91::OFFSET, s as u8 + base91::OFFSET])
+            },
+            (_, Some(range), _) => {
+                let s: f32 = ((range / 2_f32).ln() / 1.08_f32.ln()).round().clamp(0_f32, 90_f32);
+                Some(vec![b'{', s as u8 + base91::OFFSET])
+            },
+            (_, _, Some(altitude)) => {
+                comp_type.nmea_source = NmeaSource::Gga;
+                let cs: f32 = (altitude.max(1_f32).ln() / 1.002_f32.ln()).round().min(8280_f32);
+                base91::encode(cs as u32, 2)
+            },
+            _ => None,
+        };
+        match cs {
+            Some(cs) => body.extend(cs),
+            None => body.extend(b"  "),
+        }
+        body.push(comp_type.to_byte());
+
+        body
+    }
+    // DAO extension to send, which only applies to unambiguous uncompressed positions.
+    fn uncompressed_dao(&self) -> Option<Dao> {
+        match self.dao {
+            Some(dao) if !self.compressed && self.ambiguity == 0 => Some(dao),
+            _ => None,
+        }
+    }
+    // Course and speed sent in the bytes of a compressed position. Weather reports send the wind
+    // in their place.
+    fn compressed_course_speed(&self) -> Option<(u16, f32)> {
+        let (course, speed): (Option<u16>, Option<f32>) = match self.weather {
+            Some(ref weather) => (weather.wind_direction, weather::wind_speed_knots(weather)),
+            None => (self.course, self.speed),
+        };
+        Some((course?, speed?))
+    }
 }
 
+/// Source of the GPS data in a compressed position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NmeaSource {
+    Other,
+    Gll,
+    Gga,
+    Rmc,
+}
+
+/// Origin of a compressed position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompressionOrigin {
+    Compressed,
+    TncBText,
+    Software,
+    Tbd,
+    Kpc3,
+    Pico,
+    Other,
+    Digipeater,
+}
+
+/// Compression type byte of a compressed position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionType {
+    /// `true` if the GPS fix is current, `false` if it is old
+    pub current_fix: bool,
+    pub nmea_source: NmeaSource,
+    pub origin: CompressionOrigin,
+}
+
+impl CompressionType {
+    pub fn new() -> CompressionType {
+        CompressionType {
+            current_fix: true,
+            nmea_source: NmeaSource::Other,
+            origin: CompressionOrigin::Software,
+        }
+    }
+}
+
+impl Default for CompressionType {
+    fn default() -> CompressionType {
+        CompressionType::new()
+    }
+}
+
+impl CompressionType {
+    /// Decode the base-91 compression type byte.
+    pub fn from_byte(byt: u8) -> Option<CompressionType> {
+        if !base91::is_digit(byt) {
+            return None;
+        }
+        let bits: u8 = byt - base91::OFFSET;
+        let nmea_source: NmeaSource = match (bits >> 3) & 0x03 {
+            0 => NmeaSource::Other,
+            1 => NmeaSource::Gll,
+            2 => NmeaSource::Gga,
+            _ => NmeaSource::Rmc,
+        };
+        let origin: CompressionOrigin = match bits & 0x07 {
+            0 => CompressionOrigin::Compressed,
+            1 => CompressionOrigin::TncBText,
+            2 => CompressionOrigin::Software,
+            3 => CompressionOrigin::Tbd,
+            4 => CompressionOrigin::Kpc3,
+            5 => CompressionOrigin::Pico,
+            6 => CompressionOrigin::Other,
+            _ => CompressionOrigin::Digipeater,
+        };
+        Some(CompressionType {
+            current_fix: bits & 0x20 != 0,
+            nmea_source,
+            origin,
+        })
+    }
+    /// Encode as a base-91 compression type byte.
+    pub fn to_byte(&self) -> u8 {
+        let mut bits: u8 = self.origin as u8;
+        bits |= (self.nmea_source as u8) << 3;
+        if self.current_fix {
+            bits |= 0x20;
+        }
+        bits + base91::OFFSET
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Callsign {
     pub callsign: String,
     pub ssid: u8,
@@ -143,6 +490,12 @@ impl Callsign {
     }
 }
 
+impl Default for Callsign {
+    fn default() -> Callsign {
+        Callsign::new()
+    }
+}
+
 impl Callsign {
     pub fn set_callsign(&mut self, callsign: String) {
         self.callsign = callsign;
@@ -161,7 +514,7 @@ impl Callsign {
         let mut _callsign: String = String::new();
 
         if self.digi {
-            encoded_ssid = encoded_ssid | 0x80;
+            encoded_ssid |= 0x80;
         }
         for chr in self.callsign.chars() {
             _callsign.push(chr);
@@ -176,11 +529,31 @@ impl Callsign {
 
         encoded_callsign
     }
+    /// Encode in TNC2 text form, `CALL-SSID`, with a trailing `*` if digipeated.
+    pub fn encode_text(&self) -> String {
+        if self.digi {
+            format!("{}*", self)
+        } else {
+            self.to_string()
+        }
+    }
 }
 
+impl fmt::Display for Callsign {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ssid == 0 {
+            write!(f, "{}", self.callsign)
+        } else {
+            write!(f, "{}-{}", self.callsign, self.ssid)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct InformationField {
     pub data: Vec<u8>,
-    pub data_type: String,
+    /// Data type given by the first bytes of `data`, or `None` while there is no data
+    pub data_type: Option<DataType>,
     pub safe: bool,
 }
 
@@ -188,18 +561,25 @@ impl InformationField {
     pub fn new() -> InformationField {
         InformationField {
             data: Vec::new(),
-            data_type: String::new(),
+            data_type: None,
             safe: false
         }
     }
 }
 
+impl Default for InformationField {
+    fn default() -> InformationField {
+        InformationField::new()
+    }
+}
+
 impl InformationField {
     pub fn set_data(&mut self, raw_data: &[u8]) {
         self.data = raw_data.to_vec();
+        self.data_type = DataType::from_data(raw_data);
     }
-    pub fn set_data_type(&mut self, data_type: &str) {
-        self.data_type = data_type.to_string();
+    pub fn set_data_type(&mut self, data_type: DataType) {
+        self.data_type = Some(data_type);
     }
     pub fn set_safe(&mut self, safe: bool) {
         self.safe = safe;
@@ -0,0 +1,540 @@
+//! Telemetry reports
+//!
+//! A telemetry report is `T#`, a sequence number, five comma-separated analog values, eight
+//! digital bits and an optional comment, as in `T#005,199,000,255,073,123,01101001`. Older
+//! stations send `MIC` in place of the sequence number.
+//!
+//! The raw values only become meaningful with the definitions a station sends as messages
+//! addressed to itself: `PARM.` names the channels, `UNIT.` gives their units or labels, `EQNS.`
+//! gives the `a*x^2+b*x+c` coefficients of each analog channel and `BITS.` the sense of each bit
+//! and a project title. A `TelemetryContext` collects these for one station and scales its
+//! reports; `TelemetryStations` keeps a context for every station heard.
+//!
+//! Position reports may also carry base-91 compressed telemetry in their comment, which is
+//! decoded into a `CompressedTelemetry`.
+
+use std::collections::HashMap;
+use std::str;
+
+use structs::{Frame, InformationField};
+use errors::{ParseError, EncodeError};
+use message::{Message, MessageKind};
+use base91;
+use util::check_printable;
+
+
+/// Number of analog channels in a report.
+pub const ANALOG_CHANNELS: usize = 5;
+/// Number of digital bits in a report.
+pub const DIGITAL_CHANNELS: usize = 8;
+/// Maximum length of an encoded analog value.
+pub const MAX_VALUE_LEN: usize = 8;
+/// Largest value of a field in compressed telemetry.
+pub const MAX_COMPRESSED_VALUE: u32 = 8280;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Telemetry {
+    /// Sequence number, or `MIC`
+    pub sequence: String,
+    /// Raw analog values; received reports may carry fewer than five
+    pub analog: Vec<f32>,
+    /// Digital bits B1 to B8
+    pub digital: [bool; DIGITAL_CHANNELS],
+    pub comment: String,
+}
+
+impl Telemetry {
+    pub fn new() -> Telemetry {
+        Telemetry {
+            sequence: String::from("000"),
+            analog: vec![0.0; ANALOG_CHANNELS],
+            digital: [false; DIGITAL_CHANNELS],
+            comment: String::new(),
+        }
+    }
+}
+
+impl Default for Telemetry {
+    fn default() -> Telemetry {
+        Telemetry::new()
+    }
+}
+
+impl Telemetry {
+    /// Decode the telemetry carried by a received frame.
+    pub fn from_frame(frame: &Frame) -> Result<Telemetry, ParseError> {
+        Telemetry::from_info_field(&frame.info)
+    }
+
+    /// Decode a `T` information field into a telemetry report.
+    pub fn from_info_field(info: &InformationField) -> Result<Telemetry, ParseError> {
+        parse_telemetry(&info.data)
+    }
+
+    /// Encode as a `T#` information field.
+    ///
+    /// The sequence number must be up to three digits or `MIC`, there must be exactly five
+    /// analog values of at most eight characters each, and the comment may not contain `|` or
+    /// `~`. Whole values from 0 to 999 are padded to three digits.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        if self.sequence.is_empty() {
+            return Err(EncodeError::Missing { field: "sequence" });
+        }
+        if self.sequence != "MIC" {
+            if self.sequence.len() > 3 {
+                return Err(EncodeError::TooLong { field: "sequence", max: 3 });
+            }
+            if let Some(pos) = self.sequence.bytes().position(|byt| !byt.is_ascii_digit()) {
+                return Err(EncodeError::InvalidCharacter { field: "sequence", offset: pos });
+            }
+        }
+        if self.analog.len() < ANALOG_CHANNELS {
+            return Err(EncodeError::TooShort { field: "analog", min: ANALOG_CHANNELS });
+        }
+        if self.analog.len() > ANALOG_CHANNELS {
+            return Err(EncodeError::TooLong { field: "analog", max: ANALOG_CHANNELS });
+        }
+        check_printable(self.comment.as_bytes(), b"|~", "comment")?;
+        let mut info: Vec<u8> = Vec::new();
+        info.extend(b"T#");
+        info.extend(self.sequence.as_bytes());
+        for value in &self.analog {
+            let value: String = format_value(*value)
+                .ok_or(EncodeError::OutOfRange { field: "analog" })?;
+            info.push(b',');
+            info.extend(value.as_bytes());
+        }
+        info.push(b',');
+        info.extend(self.digital.iter().map(|bit| if *bit { b'1' } else { b'0' }));
+        info.extend(self.comment.as_bytes());
+
+        Ok(info)
+    }
+}
+
+/// Parse a `T#` telemetry information field.
+pub fn parse_telemetry(raw_data: &[u8]) -> Result<Telemetry, ParseError> {
+    match raw_data.first() {
+        Some(&b'T') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    if raw_data.get(1) != Some(&b'#') {
+        return Err(ParseError::InfoField { offset: 1 });
+    }
+    let data: &str = str::from_utf8(raw_data)
+        .map_err(|e| ParseError::InfoField { offset: e.valid_up_to() })?;
+
+    let mut telemetry: Telemetry = Telemetry::new();
+    telemetry.analog.clear();
+
+    // The sequence number is separated by a comma, except in some old `MIC` reports
+    let mut pos: usize = 2;
+    let seq_end: usize = match data[pos..].find(',') {
+        Some(_) if data[pos..].starts_with("MIC") => pos + 3,
+        Some(len) => pos + len,
+        None => return Err(ParseError::InfoField { offset: data.len() }),
+    };
+    if seq_end == pos {
+        return Err(ParseError::InfoField { offset: pos });
+    }
+    telemetry.sequence = data[pos..seq_end].to_string();
+    pos = if data[seq_end..].starts_with(',') { seq_end + 1 } else { seq_end };
+
+    while telemetry.analog.len() < ANALOG_CHANNELS {
+        let end: usize = data[pos..].find(',').map_or(data.len(), |len| pos + len);
+        let value: f32 = data[pos..end].trim().parse()
+            .map_err(|_| ParseError::InfoField { offset: pos })?;
+        telemetry.analog.push(value);
+        if end == data.len() {
+            return Ok(telemetry);
+        }
+        pos = end + 1;
+    }
+
+    let bits: &[u8] = &raw_data[pos..];
+    if bits.len() < DIGITAL_CHANNELS {
+        return Err(ParseError::InfoField { offset: raw_data.len() });
+    }
+    for (ind, byt) in bits[..DIGITAL_CHANNELS].iter().enumerate() {
+        telemetry.digital[ind] = match *byt {
+            b'0' => false,
+            b'1' => true,
+            _ => return Err(ParseError::InfoField { offset: pos + ind }),
+        };
+    }
+    telemetry.comment = data[(pos + DIGITAL_CHANNELS)..].to_string();
+    Ok(telemetry)
+}
+
+// Format an analog value in at most eight characters.
+fn format_value(value: f32) -> Option<String> {
+    let text: String = if value.fract() == 0.0 && (0.0..=999.0).contains(&value) {
+        format!("{:03}", value as u16)
+    } else {
+        format!("{}", value)
+    };
+    if !value.is_finite() || text.len() > MAX_VALUE_LEN {
+        return None;
+    }
+    Some(text)
+}
+
+/// Coefficients of the `a*x^2+b*x+c` equation that scales an analog channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Equation {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+}
+
+impl Equation {
+    /// The identity equation, which leaves raw values unchanged.
+    pub fn new() -> Equation {
+        Equation { a: 0.0, b: 1.0, c: 0.0 }
+    }
+}
+
+impl Default for Equation {
+    fn default() -> Equation {
+        Equation::new()
+    }
+}
+
+impl Equation {
+    pub fn apply(&self, value: f32) -> f32 {
+        self.a * value * value + self.b * value + self.c
+    }
+}
+
+/// Telemetry definition sent as a message addressed to the station it describes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Definition {
+    /// `PARM.` channel names, analog channels first
+    Parameters(Vec<String>),
+    /// `UNIT.` analog units followed by digital labels
+    Units(Vec<String>),
+    /// `EQNS.` coefficients of the analog channels
+    Equations(Vec<Equation>),
+    /// `BITS.` value of each bit that means active, and the project title
+    Bits { sense: [bool; DIGITAL_CHANNELS], project: String },
+}
+
+impl Definition {
+    /// Decode the definition carried by a message. Error offsets are relative to the text.
+    pub fn from_message(message: &Message) -> Result<Definition, ParseError> {
+        if message.kind != MessageKind::Message {
+            return Err(ParseError::InfoField { offset: 0 });
+        }
+        parse_definition(&message.text)
+    }
+
+    /// Build the message that sends this definition for a station.
+    pub fn to_message(&self, station: &str) -> Message {
+        let text: String = match *self {
+            Definition::Parameters(ref names) => format!("PARM.{}", names.join(",")),
+            Definition::Units(ref units) => format!("UNIT.{}", units.join(",")),
+            Definition::Equations(ref equations) => {
+                let coefficients: Vec<String> = equations.iter()
+                    .map(|eq| format!("{},{},{}", eq.a, eq.b, eq.c))
+                    .collect();
+                format!("EQNS.{}", coefficients.join(","))
+            },
+            Definition::Bits { ref sense, ref project } => {
+                let bits: String = sense.iter().map(|bit| if *bit { '1' } else { '0' }).collect();
+                format!("BITS.{},{}", bits, project)
+            },
+        };
+        let mut message: Message = Message::new();
+        message.addressee = station.to_string();
+        message.text = text;
+        message
+    }
+}
+
+/// Parse the text of a `PARM.`, `UNIT.`, `EQNS.` or `BITS.` message.
+pub fn parse_definition(text: &str) -> Result<Definition, ParseError> {
+    if text.len() < 5 || !text.is_char_boundary(5) {
+        return Err(ParseError::InfoField { offset: 0 });
+    }
+    let (prefix, list): (&str, &str) = text.split_at(5);
+    let list: &str = list.trim_end();
+    let fields: Vec<&str> = list.split(',').collect();
+
+    match prefix {
+        "PARM." => Ok(Definition::Parameters(fields.iter().map(|field| field.to_string()).collect())),
+        "UNIT." => Ok(Definition::Units(fields.iter().map(|field| field.to_string()).collect())),
+        "EQNS." => {
+            let mut coefficients: Vec<f32> = Vec::new();
+            let mut offset: usize = 5;
+            for field in &fields {
+                let value: f32 = match field.trim() {
+                    "" => 0.0,
+                    value => value.parse().map_err(|_| ParseError::InfoField { offset })?,
+                };
+                coefficients.push(value);
+                offset += field.len() + 1;
+            }
+            Ok(Definition::Equations(coefficients.chunks(3).map(|chunk| Equation {
+                a: chunk[0],
+                b: chunk.get(1).cloned().unwrap_or(1.0),
+                c: chunk.get(2).cloned().unwrap_or(0.0),
+            }).collect()))
+        },
+        "BITS." => {
+            let bits: &[u8] = fields[0].as_bytes();
+            if bits.len() != DIGITAL_CHANNELS {
+                return Err(ParseError::InfoField { offset: 5 + bits.len().min(DIGITAL_CHANNELS) });
+            }
+            let mut sense: [bool; DIGITAL_CHANNELS] = [true; DIGITAL_CHANNELS];
+            for (ind, byt) in bits.iter().enumerate() {
+                sense[ind] = match *byt {
+                    b'0' => false,
+                    b'1' => true,
+                    _ => return Err(ParseError::InfoField { offset: 5 + ind }),
+                };
+            }
+            let project: String = list.get((DIGITAL_CHANNELS + 1)..).unwrap_or("").to_string();
+            Ok(Definition::Bits { sense, project })
+        },
+        _ => Err(ParseError::InfoField { offset: 0 }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalogReading {
+    pub name: String,
+    pub unit: String,
+    /// Value after applying the channel's equation
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigitalReading {
+    pub name: String,
+    pub label: String,
+    /// Whether the bit matches its sense
+    pub active: bool,
+}
+
+/// Telemetry report scaled and labelled with a station's definitions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub sequence: String,
+    pub analog: Vec<AnalogReading>,
+    pub digital: Vec<DigitalReading>,
+    pub comment: String,
+}
+
+/// Definitions received for one station.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryContext {
+    /// Channel names, analog channels first; empty names fall back to `A1`-`A5` and `B1`-`B8`
+    pub names: Vec<String>,
+    /// Analog units followed by digital labels
+    pub units: Vec<String>,
+    pub equations: Vec<Equation>,
+    pub sense: [bool; DIGITAL_CHANNELS],
+    pub project: String,
+}
+
+impl TelemetryContext {
+    /// Context with no names or units, identity equations and every bit active when set.
+    pub fn new() -> TelemetryContext {
+        TelemetryContext {
+            names: Vec::new(),
+            units: Vec::new(),
+            equations: vec![Equation::new(); ANALOG_CHANNELS],
+            sense: [true; DIGITAL_CHANNELS],
+            project: String::new(),
+        }
+    }
+}
+
+impl Default for TelemetryContext {
+    fn default() -> TelemetryContext {
+        TelemetryContext::new()
+    }
+}
+
+impl TelemetryContext {
+    /// Replace the part of the context a definition covers.
+    pub fn apply(&mut self, definition: &Definition) {
+        match *definition {
+            Definition::Parameters(ref names) => self.names = names.clone(),
+            Definition::Units(ref units) => self.units = units.clone(),
+            Definition::Equations(ref equations) => {
+                self.equations = equations.clone();
+                self.equations.resize(ANALOG_CHANNELS, Equation::new());
+            },
+            Definition::Bits { ref sense, ref project } => {
+                self.sense = *sense;
+                self.project = project.clone();
+            },
+        }
+    }
+
+    /// Scale and label a telemetry report.
+    pub fn scale(&self, telemetry: &Telemetry) -> Reading {
+        let label = |list: &Vec<String>, ind: usize| list.get(ind).cloned().unwrap_or_default();
+        let name = |ind: usize, default: String| match self.names.get(ind) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => default,
+        };
+        Reading {
+            sequence: telemetry.sequence.clone(),
+            analog: telemetry.analog.iter().enumerate().map(|(ind, value)| AnalogReading {
+                name: name(ind, format!("A{}", ind + 1)),
+                unit: label(&self.units, ind),
+                value: self.equations.get(ind).cloned().unwrap_or_default().apply(*value),
+            }).collect(),
+            digital: telemetry.digital.iter().enumerate().map(|(ind, bit)| DigitalReading {
+                name: name(ANALOG_CHANNELS + ind, format!("B{}", ind + 1)),
+                label: label(&self.units, ANALOG_CHANNELS + ind),
+                active: *bit == self.sense[ind],
+            }).collect(),
+            comment: telemetry.comment.clone(),
+        }
+    }
+}
+
+/// Telemetry contexts of every station heard, keyed by callsign.
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryStations {
+    contexts: HashMap<String, TelemetryContext>,
+}
+
+impl TelemetryStations {
+    pub fn new() -> TelemetryStations {
+        TelemetryStations {
+            contexts: HashMap::new(),
+        }
+    }
+}
+
+impl TelemetryStations {
+    pub fn context(&self, station: &str) -> Option<&TelemetryContext> {
+        self.contexts.get(&station.to_uppercase())
+    }
+
+    /// Process a received frame.
+    ///
+    /// Definition messages update the context of their addressee. Telemetry reports are
+    /// returned scaled with the context of their source.
+    pub fn receive(&mut self, frame: &Frame) -> Option<Reading> {
+        if let Ok(message) = Message::from_frame(frame) {
+            if let Ok(definition) = Definition::from_message(&message) {
+                self.contexts.entry(message.addressee.to_uppercase())
+                    .or_default()
+                    .apply(&definition);
+            }
+            return None;
+        }
+        let telemetry: Telemetry = Telemetry::from_frame(frame).ok()?;
+        let station: String = frame.source.to_string().to_uppercase();
+        Some(match self.contexts.get(&station) {
+            Some(context) => context.scale(&telemetry),
+            None => TelemetryContext::new().scale(&telemetry),
+        })
+    }
+}
+
+/// Telemetry carried between `|` characters in the comment of a position report.
+///
+/// The block is a base-91 sequence number, up to five analog values and, only after all five,
+/// the digital bits, each two characters long, as in `|ss11223344556677|`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressedTelemetry {
+    pub sequence: u16,
+    pub analog: Vec<u16>,
+    /// Digital bits B1 to B8, if sent
+    pub digital: Option<[bool; DIGITAL_CHANNELS]>,
+}
+
+impl CompressedTelemetry {
+    pub fn new() -> CompressedTelemetry {
+        CompressedTelemetry {
+            sequence: 0,
+            analog: Vec::new(),
+            digital: None,
+        }
+    }
+}
+
+impl Default for CompressedTelemetry {
+    fn default() -> CompressedTelemetry {
+        CompressedTelemetry::new()
+    }
+}
+
+impl CompressedTelemetry {
+    /// Decode the characters between the pipes of a telemetry block.
+    pub fn decode(block: &[u8]) -> Option<CompressedTelemetry> {
+        if block.len() < 4 || block.len() > 14 || !block.len().is_multiple_of(2) {
+            return None;
+        }
+        let mut values: Vec<u16> = Vec::new();
+        for pair in block.chunks(2) {
+            values.push(base91::decode(pair)? as u16);
+        }
+        let mut telemetry: CompressedTelemetry = CompressedTelemetry::new();
+        telemetry.sequence = values.remove(0);
+        if values.len() > ANALOG_CHANNELS {
+            let byte: u16 = values.pop()?;
+            let mut digital: [bool; DIGITAL_CHANNELS] = [false; DIGITAL_CHANNELS];
+            for (ind, bit) in digital.iter_mut().enumerate() {
+                *bit = byte & (1 << ind) != 0;
+            }
+            telemetry.digital = Some(digital);
+        }
+        telemetry.analog = values;
+        Some(telemetry)
+    }
+
+    /// Encode as a telemetry block, including the pipes.
+    ///
+    /// The sequence number wraps at 8281 and analog values are clamped to 8280. Only the first
+    /// five analog channels are sent; when digital bits are set, missing channels are sent as 0.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut values: Vec<u32> = vec![u32::from(self.sequence) % (MAX_COMPRESSED_VALUE + 1)];
+        values.extend(self.analog.iter().take(ANALOG_CHANNELS)
+                      .map(|value| u32::from(*value).min(MAX_COMPRESSED_VALUE)));
+        if let Some(digital) = self.digital {
+            values.resize(ANALOG_CHANNELS + 1, 0);
+            values.push(digital.iter().enumerate()
+                        .fold(0, |byte, (ind, bit)| if *bit { byte | 1 << ind } else { byte }));
+        }
+        // A block needs at least one analog value
+        if values.len() < 2 {
+            values.push(0);
+        }
+        let mut block: Vec<u8> = vec![b'|'];
+        for value in values {
+            block.extend(base91::encode(value, 2).unwrap_or_default());
+        }
+        block.push(b'|');
+        block
+    }
+
+    /// Convert to an uncompressed report so it can be scaled with a `TelemetryContext`.
+    pub fn to_telemetry(&self) -> Telemetry {
+        Telemetry {
+            sequence: format!("{:03}", self.sequence),
+            analog: self.analog.iter().map(|value| f32::from(*value)).collect(),
+            digital: self.digital.unwrap_or([false; DIGITAL_CHANNELS]),
+            comment: String::new(),
+        }
+    }
+}
+
+/// Find a telemetry block in a comment and return it with the rest of the comment.
+pub fn extract_compressed_telemetry(comment: &[u8]) -> Option<(CompressedTelemetry, Vec<u8>)> {
+    let start: usize = comment.iter().position(|byt| *byt == b'|')?;
+    let len: usize = comment[(start + 1)..].iter().position(|byt| *byt == b'|')?;
+    let end: usize = start + 1 + len;
+    let telemetry: CompressedTelemetry = CompressedTelemetry::decode(&comment[(start + 1)..end])?;
+
+    let mut rest: Vec<u8> = comment[..start].to_vec();
+    rest.extend(&comment[(end + 1)..]);
+    Some((telemetry, rest))
+}
@@ -0,0 +1,153 @@
+//! Third-party traffic
+//!
+//! A station that relays a packet it did not originate, such as an igate passing APRS-IS traffic
+//! to RF, wraps it as a `}` information field holding the original packet in TNC2 text form:
+//! `N0CALL>APRS,WIDE1-1:}W1AW>APRS,TCPIP,N0CALL*:>Status text`. The inner header's path ends with
+//! `TCPIP` and the callsign of the igate, so the packet is not gated back to APRS-IS.
+//!
+//! Relayed traffic may itself be third-party traffic. Decoding follows up to `MAX_NESTING` levels
+//! of wrapping; only the header of each relaying station is kept, as the rest of its frame is
+//! the next level.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use aprs::functions;
+//! # use aprs::third_party::ThirdParty;
+//! # fn main() {
+//! let frame = functions::parse_frame(b"N0CALL>APRS,WIDE1-1:}W1AW>APRS,TCPIP,N0CALL*:>Status text").unwrap();
+//! let third_party = ThirdParty::from_frame(&frame).unwrap();
+//! assert_eq!(third_party.inner.source.callsign, "W1AW");
+//! assert_eq!(third_party.inner.info.data, b">Status text".to_vec());
+//!
+//! let wrapped = ThirdParty::wrap(&third_party.inner, &frame.source, &frame.destination, &frame.path);
+//! assert_eq!(wrapped.frame(), frame);
+//! # }
+//! ```
+
+use structs::{Frame, Callsign, InformationField};
+use errors::ParseError;
+use functions;
+
+
+/// Deepest nesting of third-party traffic that is decoded.
+pub const MAX_NESTING: usize = 8;
+
+/// Addresses of a frame, without its information field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub source: Callsign,
+    pub destination: Callsign,
+    pub path: Vec<Callsign>,
+}
+
+impl Header {
+    pub fn from_frame(frame: &Frame) -> Header {
+        Header {
+            source: frame.source.clone(),
+            destination: frame.destination.clone(),
+            path: frame.path.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThirdParty {
+    /// Header of the station that relayed the traffic
+    pub outer: Header,
+    /// Relayed frame, decoded from the inner header and payload
+    pub inner: Frame,
+    /// Next level of wrapping, when the relayed frame is itself third-party traffic
+    pub nested: Option<Box<ThirdParty>>,
+}
+
+impl ThirdParty {
+    /// Decode the third-party traffic carried by a received frame.
+    ///
+    /// Traffic wrapped more than `MAX_NESTING` times is rejected.
+    pub fn from_frame(frame: &Frame) -> Result<ThirdParty, ParseError> {
+        let data: &[u8] = &frame.info.data;
+        let mut inners: Vec<Frame> = Vec::new();
+        // Offsets of errors in nested traffic are relative to the outermost information field
+        let mut start: usize = 0;
+        loop {
+            let inner: Frame = parse_third_party(&data[start..]).map_err(|e| e.shift(start))?;
+            start = data.len() - inner.info.data.len();
+            let wrapped: bool = inner.info.data.first() == Some(&b'}');
+            inners.push(inner);
+            if !wrapped {
+                break;
+            }
+            if inners.len() == MAX_NESTING {
+                return Err(ParseError::InfoField { offset: start });
+            }
+        }
+
+        let mut third_party: Option<ThirdParty> = None;
+        while let Some(inner) = inners.pop() {
+            let outer: Header = Header::from_frame(inners.last().unwrap_or(frame));
+            third_party = Some(ThirdParty { outer, inner, nested: third_party.map(Box::new) });
+        }
+        third_party.ok_or(ParseError::InfoField { offset: 0 })
+    }
+
+    /// Wrap a frame received from APRS-IS for transmission by an igate.
+    ///
+    /// The inner header keeps the frame's source and destination and replaces its path with
+    /// `TCPIP` and the igate's callsign. The outer frame is sent by the igate to `destination`
+    /// over `path`.
+    pub fn wrap(frame: &Frame, igate: &Callsign, destination: &Callsign, path: &[Callsign]) -> ThirdParty {
+        let mut tcpip: Callsign = Callsign::new();
+        tcpip.set_callsign(String::from("TCPIP"));
+        let mut gate: Callsign = igate.clone();
+        gate.set_digi(true);
+
+        let mut inner: Frame = frame.clone();
+        inner.path = vec![tcpip, gate];
+
+        let nested: Option<Box<ThirdParty>> = ThirdParty::from_frame(&inner).ok().map(Box::new);
+        ThirdParty {
+            outer: Header {
+                source: igate.clone(),
+                destination: destination.clone(),
+                path: path.to_vec(),
+            },
+            inner,
+            nested,
+        }
+    }
+
+    /// Outer frame, carrying the relayed frame as a `}` information field.
+    pub fn frame(&self) -> Frame {
+        let mut data: Vec<u8> = vec![b'}'];
+        data.extend(self.inner.encode_text());
+        let mut info: InformationField = InformationField::new();
+        info.set_data(&data);
+        info.set_safe(true);
+        Frame {
+            source: self.outer.source.clone(),
+            destination: self.outer.destination.clone(),
+            path: self.outer.path.clone(),
+            info,
+        }
+    }
+
+    /// Innermost relayed frame, the one sent by the originating station.
+    pub fn origin(&self) -> &Frame {
+        match self.nested {
+            Some(ref nested) => nested.origin(),
+            None => &self.inner,
+        }
+    }
+}
+
+/// Parse a `}` third-party information field into the relayed frame.
+///
+/// Errors in the inner header or payload have offsets within the information field.
+pub fn parse_third_party(raw_data: &[u8]) -> Result<Frame, ParseError> {
+    match raw_data.first() {
+        Some(&b'}') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    functions::parse_frame_text(&raw_data[1..]).map_err(|e| e.shift(1))
+}
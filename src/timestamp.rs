@@ -0,0 +1,219 @@
+//! APRS timestamps
+//!
+//! Reports carry one of four timestamp formats:
+//!
+//! * `DDHHMMz`: day of the month, hour and minute in UTC
+//! * `DDHHMM/`: day of the month, hour and minute in the sender's local time
+//! * `HHMMSSh`: hour, minute and second in UTC
+//! * `MMDDHHMM`: month, day, hour and minute in UTC, used by positionless weather reports
+//!
+//! None of them carries a full date. `Timestamp::resolve` completes one with the month, day or
+//! year that puts it closest to a reference time, so reports from just before midnight or the
+//! end of a month or year resolve correctly.
+//!
+//! ```rust
+//! # extern crate aprs;
+//! # use std::time::Duration;
+//! # use aprs::timestamp::Timestamp;
+//! # fn main() {
+//! let timestamp = Timestamp::parse(b"312359z").unwrap();
+//! // 2024-01-01 00:05 UTC
+//! let now = Duration::from_secs(1_704_067_500);
+//! // 2023-12-31 23:59 UTC
+//! assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(1_704_067_140)));
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use errors::ParseError;
+
+
+const SECS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timestamp {
+    /// `DDHHMMz`
+    DhmZulu { day: u8, hour: u8, minute: u8 },
+    /// `DDHHMM/`
+    DhmLocal { day: u8, hour: u8, minute: u8 },
+    /// `HHMMSSh`
+    Hms { hour: u8, minute: u8, second: u8 },
+    /// `MMDDHHMM`
+    Mdhm { month: u8, day: u8, hour: u8, minute: u8 },
+}
+
+impl Timestamp {
+    /// Parse a timestamp at the start of `raw_data`.
+    ///
+    /// Six digits followed by `z`, `/` or `h` are read as a seven-character timestamp and eight
+    /// digits as `MMDDHHMM`. Day 0 is accepted, since placeholders such as `000000z` are common,
+    /// but cannot be resolved. Errors are `ParseError::InfoField` at the offending byte.
+    pub fn parse(raw_data: &[u8]) -> Result<Timestamp, ParseError> {
+        if raw_data.len() < 7 {
+            return Err(ParseError::InfoField { offset: raw_data.len() });
+        }
+        if let Some(pos) = raw_data[..6].iter().position(|byt| !byt.is_ascii_digit()) {
+            return Err(ParseError::InfoField { offset: pos });
+        }
+        let pair = |ind: usize| (raw_data[ind] - b'0') * 10 + raw_data[ind + 1] - b'0';
+        let (first, second, third): (u8, u8, u8) = (pair(0), pair(2), pair(4));
+
+        let timestamp: Timestamp = match raw_data[6] {
+            b'z' => Timestamp::DhmZulu { day: first, hour: second, minute: third },
+            b'/' => Timestamp::DhmLocal { day: first, hour: second, minute: third },
+            b'h' => Timestamp::Hms { hour: first, minute: second, second: third },
+            byt if byt.is_ascii_digit() => {
+                match raw_data.get(7) {
+                    Some(byt) if byt.is_ascii_digit() => {},
+                    _ => return Err(ParseError::InfoField { offset: 7 }),
+                }
+                Timestamp::Mdhm { month: first, day: second, hour: third, minute: pair(6) }
+            },
+            _ => return Err(ParseError::InfoField { offset: 6 }),
+        };
+        match timestamp.invalid_field() {
+            Some(offset) => Err(ParseError::InfoField { offset }),
+            None => Ok(timestamp),
+        }
+    }
+
+    /// `DDHHMMz` timestamp of an instant.
+    pub fn dhm_zulu(instant: Duration) -> Timestamp {
+        let (_, _, day, hour, minute, _) = split(instant);
+        Timestamp::DhmZulu { day, hour, minute }
+    }
+
+    /// `HHMMSSh` timestamp of an instant.
+    pub fn hms(instant: Duration) -> Timestamp {
+        let (_, _, _, hour, minute, second) = split(instant);
+        Timestamp::Hms { hour, minute, second }
+    }
+
+    /// `MMDDHHMM` timestamp of an instant.
+    pub fn mdhm(instant: Duration) -> Timestamp {
+        let (_, month, day, hour, minute, _) = split(instant);
+        Timestamp::Mdhm { month, day, hour, minute }
+    }
+
+    /// Complete the timestamp with the date that puts it closest to `now`, both as time since
+    /// the Unix epoch.
+    ///
+    /// Returns `None` for local times, whose offset from UTC is unknown, and for days that do
+    /// not exist in any nearby month or year.
+    pub fn resolve(&self, now: Duration) -> Option<Duration> {
+        let now_secs: i64 = now.as_secs() as i64;
+        let today: i64 = now_secs.div_euclid(SECS_PER_DAY);
+        let (year, month, _): (i64, u32, u32) = civil_from_days(today);
+
+        let candidates: Vec<i64> = match *self {
+            Timestamp::DhmZulu { day, hour, minute } => {
+                (-1..=1).filter_map(|offset: i64| {
+                    let months: i64 = year * 12 + i64::from(month) - 1 + offset;
+                    let (year, month): (i64, u32) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+                    instant(year, month, day, hour, minute, 0)
+                }).collect()
+            },
+            Timestamp::DhmLocal { .. } => return None,
+            Timestamp::Hms { hour, minute, second } => {
+                (-1..=1).map(|offset: i64| {
+                    (today + offset) * SECS_PER_DAY + i64::from(hour) * 3600 +
+                        i64::from(minute) * 60 + i64::from(second)
+                }).collect()
+            },
+            Timestamp::Mdhm { month, day, hour, minute } => {
+                (-1..=1).filter_map(|offset: i64| {
+                    instant(year + offset, u32::from(month), day, hour, minute, 0)
+                }).collect()
+            },
+        };
+        candidates.into_iter()
+            .filter(|secs| *secs >= 0)
+            .min_by_key(|secs| (secs - now_secs).abs())
+            .map(|secs| Duration::from_secs(secs as u64))
+    }
+
+    // Offset of the first field outside its range.
+    fn invalid_field(&self) -> Option<usize> {
+        let (fields, limits): ([u8; 4], [u8; 4]) = match *self {
+            Timestamp::DhmZulu { day, hour, minute } |
+            Timestamp::DhmLocal { day, hour, minute } => ([day, hour, minute, 0], [31, 23, 59, 0]),
+            Timestamp::Hms { hour, minute, second } => ([hour, minute, second, 0], [23, 59, 59, 0]),
+            Timestamp::Mdhm { month, day, hour, minute } => {
+                if month == 0 {
+                    return Some(0);
+                }
+                ([month, day, hour, minute], [12, 31, 23, 59])
+            },
+        };
+        fields.iter().zip(limits.iter()).position(|(field, limit)| field > limit).map(|ind| ind * 2)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Timestamp::DhmZulu { day, hour, minute } => write!(f, "{:02}{:02}{:02}z", day, hour, minute),
+            Timestamp::DhmLocal { day, hour, minute } => write!(f, "{:02}{:02}{:02}/", day, hour, minute),
+            Timestamp::Hms { hour, minute, second } => write!(f, "{:02}{:02}{:02}h", hour, minute, second),
+            Timestamp::Mdhm { month, day, hour, minute } => {
+                write!(f, "{:02}{:02}{:02}{:02}", month, day, hour, minute)
+            },
+        }
+    }
+}
+
+// Seconds since the epoch of a UTC date and time, if the day exists.
+fn instant(year: i64, month: u32, day: u8, hour: u8, minute: u8, second: u8) -> Option<i64> {
+    if day == 0 || u32::from(day) > days_in_month(year, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, u32::from(day)) * SECS_PER_DAY + i64::from(hour) * 3600 +
+         i64::from(minute) * 60 + i64::from(second))
+}
+
+// (year, month, day, hour, minute, second) of an instant.
+fn split(instant: Duration) -> (i64, u8, u8, u8, u8, u8) {
+    let secs: i64 = instant.as_secs() as i64;
+    let (year, month, day): (i64, u32, u32) = civil_from_days(secs / SECS_PER_DAY);
+    let time: i64 = secs % SECS_PER_DAY;
+    (year, month as u8, day as u8, (time / 3600) as u8, (time % 3600 / 60) as u8, (time % 60) as u8)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year: i64 = if month <= 2 { year - 1 } else { year };
+    let era: i64 = year.div_euclid(400);
+    let year_of_era: i64 = year - era * 400;
+    let month: i64 = i64::from(month);
+    let day_of_year: i64 = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 +
+        i64::from(day) - 1;
+    let day_of_era: i64 = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Proleptic Gregorian date of a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days: i64 = days + 719_468;
+    let era: i64 = days.div_euclid(146_097);
+    let day_of_era: i64 = days - era * 146_097;
+    let year_of_era: i64 = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 -
+                            day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp: i64 = (5 * day_of_year + 2) / 153;
+    let day: u32 = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month: u32 = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year: i64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -1,5 +1,8 @@
 //! Utilities for validating data.
 
+use errors::EncodeError;
+
+
 /// Validates an over-the-air callsign. APRS-IS is more forgiving.
 ///
 /// Valid callsigns are between 4 and 7 alphanumeric characters with at most a single hyphen `-`
@@ -134,3 +137,12 @@ pub fn aprspass(callsign: &str) -> Option<u16> {
     }
     Some(hash & 0x7fff)
 }
+
+/// Check that text is printable ASCII, from space to `~`, without any of the `reserved` bytes.
+pub(crate) fn check_printable(text: &[u8], reserved: &[u8], field: &'static str)
+    -> Result<(), EncodeError> {
+    match text.iter().position(|byt| reserved.contains(byt) || !(0x20..0x7f).contains(byt)) {
+        Some(offset) => Err(EncodeError::InvalidCharacter { field, offset }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,489 @@
+//! Weather reports
+//!
+//! A positionless weather report is a `_`, an `MMDDHHMM` timestamp, wind direction and speed as
+//! `cDDDsSSS`, and a series of fields that are each a letter and a fixed number of digits, as in
+//! `_10090556c220s004g005t077r000p000P000h50b09900wRSW`. Anything after the last field, usually
+//! the software and station type, is kept as the comment.
+//!
+//! Position reports with the `_` symbol carry the same fields after the position. The wind is
+//! sent as a `DDD/SSS` course and speed in uncompressed positions, and in the course and speed
+//! bytes of compressed ones.
+//!
+//! Values are stored in the units of the protocol: degrees, miles per hour, degrees Fahrenheit,
+//! hundredths of an inch of rain, tenths of a millibar and inches of snow. Accessors convert
+//! them to metric units. Fields sent as dots or spaces are unknown and decode to `None`.
+//!
+//! Raw data forwarded from Peet Bros stations is decoded into the same struct. These reports are
+//! groups of hex digits: `#W1` or `*` followed by wind direction (two digits, 0-255 for a full
+//! circle), wind speed, temperature in tenths of a degree Fahrenheit and long-term rain total in
+//! hundredths of an inch. Wind speed is in tenths of a mile per hour after `#W1` and tenths of a
+//! kilometre per hour after `*`. Ultimeter `$ULTW` packets are thirteen or more four-digit hex
+//! fields, with `----` for values the station does not have, and `!!` logging mode packets are
+//! twelve or more such fields in a different order.
+
+use std::str;
+
+use structs::{Frame, InformationField, PositionFrame};
+use errors::{ParseError, EncodeError};
+use timestamp::Timestamp;
+use util::check_printable;
+
+
+const KNOTS_PER_MPH: f32 = 0.868_976;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    /// `MMDDHHMM` timestamp of a positionless report
+    pub timestamp: Option<Timestamp>,
+    /// Wind direction in degrees
+    pub wind_direction: Option<u16>,
+    /// Sustained one-minute wind speed in miles per hour
+    pub wind_speed: Option<u16>,
+    /// Gust over the last five minutes in miles per hour
+    pub wind_gust: Option<u16>,
+    /// Temperature in degrees Fahrenheit
+    pub temperature: Option<i16>,
+    /// Rain over the last hour in hundredths of an inch
+    pub rain_1h: Option<u16>,
+    /// Rain over the last 24 hours in hundredths of an inch
+    pub rain_24h: Option<u16>,
+    /// Rain since midnight in hundredths of an inch
+    pub rain_midnight: Option<u16>,
+    /// Relative humidity in percent
+    pub humidity: Option<u8>,
+    /// Barometric pressure in tenths of a millibar
+    pub pressure: Option<u32>,
+    /// Luminosity in watts per square metre
+    pub luminosity: Option<u16>,
+    /// Snowfall over the last 24 hours in inches
+    pub snow: Option<f32>,
+    /// Raw rain counter
+    pub rain_raw: Option<u16>,
+    pub comment: Vec<u8>,
+}
+
+impl Weather {
+    pub fn new() -> Weather {
+        Weather {
+            timestamp: None,
+            wind_direction: None,
+            wind_speed: None,
+            wind_gust: None,
+            temperature: None,
+            rain_1h: None,
+            rain_24h: None,
+            rain_midnight: None,
+            humidity: None,
+            pressure: None,
+            luminosity: None,
+            snow: None,
+            rain_raw: None,
+            comment: Vec::new(),
+        }
+    }
+}
+
+impl Default for Weather {
+    fn default() -> Weather {
+        Weather::new()
+    }
+}
+
+impl Weather {
+    /// Decode the weather carried by a received frame, either as a positionless report or as a
+    /// position report with the weather symbol.
+    pub fn from_frame(frame: &Frame) -> Result<Weather, ParseError> {
+        Weather::from_info_field(&frame.info)
+    }
+
+    /// Decode a `_`, Peet Bros or Ultimeter information field, or a position information field
+    /// with weather.
+    pub fn from_info_field(info: &InformationField) -> Result<Weather, ParseError> {
+        match info.data.first() {
+            Some(&b'_') => return parse_weather(&info.data),
+            Some(&b'#') | Some(&b'*') => return parse_peet_bros(&info.data),
+            Some(&b'$') => return parse_ultimeter(&info.data),
+            Some(&b'!') if info.data.starts_with(b"!!") => return parse_ultimeter(&info.data),
+            _ => {},
+        }
+        let position: PositionFrame = PositionFrame::from_info_field(info)?;
+        match position.weather {
+            Some(weather) => Ok(weather),
+            None => Err(ParseError::InfoField { offset: info.data.len() }),
+        }
+    }
+
+    /// Encode as a `_` positionless weather information field.
+    ///
+    /// The timestamp must be `MMDDHHMM` and the comment must be printable. Values too large
+    /// for their field are clamped, and unknown wind, gust and temperature are sent as dots.
+    pub fn create_info_field(&self) -> Result<Vec<u8>, EncodeError> {
+        let timestamp: Timestamp = match self.timestamp {
+            Some(timestamp @ Timestamp::Mdhm { .. }) => timestamp,
+            Some(_) => return Err(EncodeError::TooShort { field: "timestamp", min: 8 }),
+            None => return Err(EncodeError::Missing { field: "timestamp" }),
+        };
+        check_printable(&self.comment, b"", "comment")?;
+        let mut info: Vec<u8> = Vec::new();
+        info.push(b'_');
+        info.extend(timestamp.to_string().as_bytes());
+        info.extend(encode_field(b'c', self.wind_direction.map(i32::from), 3));
+        info.extend(encode_field(b's', self.wind_speed.map(i32::from), 3));
+        info.extend(self.encode_fields());
+        info.extend(&self.comment);
+
+        Ok(info)
+    }
+
+    /// Encode the fields that follow the position in a position report with weather.
+    ///
+    /// Uncompressed positions start with the wind as `DDD/SSS`; compressed positions carry it in
+    /// the course and speed bytes instead.
+    pub fn create_position_fields(&self, compressed: bool) -> Vec<u8> {
+        let mut fields: Vec<u8> = Vec::new();
+        if !compressed {
+            fields.extend(&encode_field(b'c', self.wind_direction.map(i32::from), 3)[1..]);
+            fields.push(b'/');
+            fields.extend(&encode_field(b's', self.wind_speed.map(i32::from), 3)[1..]);
+        }
+        fields.extend(self.encode_fields());
+        fields
+    }
+
+    /// Wind speed in metres per second.
+    pub fn wind_speed_ms(&self) -> Option<f32> {
+        self.wind_speed.map(|speed| f32::from(speed) * 0.447_04)
+    }
+    /// Wind gust in metres per second.
+    pub fn wind_gust_ms(&self) -> Option<f32> {
+        self.wind_gust.map(|gust| f32::from(gust) * 0.447_04)
+    }
+    /// Temperature in degrees Celsius.
+    pub fn temperature_celsius(&self) -> Option<f32> {
+        self.temperature.map(|temp| (f32::from(temp) - 32.0) * 5.0 / 9.0)
+    }
+    /// Rain over the last hour in millimetres.
+    pub fn rain_1h_mm(&self) -> Option<f32> {
+        self.rain_1h.map(|rain| f32::from(rain) * 0.254)
+    }
+    /// Rain over the last 24 hours in millimetres.
+    pub fn rain_24h_mm(&self) -> Option<f32> {
+        self.rain_24h.map(|rain| f32::from(rain) * 0.254)
+    }
+    /// Rain since midnight in millimetres.
+    pub fn rain_midnight_mm(&self) -> Option<f32> {
+        self.rain_midnight.map(|rain| f32::from(rain) * 0.254)
+    }
+    /// Barometric pressure in hectopascals.
+    pub fn pressure_hpa(&self) -> Option<f32> {
+        self.pressure.map(|pressure| pressure as f32 / 10.0)
+    }
+    /// Snowfall in centimetres.
+    pub fn snow_cm(&self) -> Option<f32> {
+        self.snow.map(|snow| snow * 2.54)
+    }
+
+    // Fields after the wind, up to the comment.
+    fn encode_fields(&self) -> Vec<u8> {
+        let mut fields: Vec<u8> = Vec::new();
+        fields.extend(encode_field(b'g', self.wind_gust.map(i32::from), 3));
+        fields.extend(encode_field(b't', self.temperature.map(i32::from), 3));
+        for &(letter, value) in &[(b'r', self.rain_1h), (b'p', self.rain_24h), (b'P', self.rain_midnight)] {
+            if value.is_some() {
+                fields.extend(encode_field(letter, value.map(i32::from), 3));
+            }
+        }
+        if let Some(humidity) = self.humidity {
+            // 100% is sent as 00
+            fields.extend(encode_field(b'h', Some(i32::from(humidity.min(100) % 100)), 2));
+        }
+        if let Some(pressure) = self.pressure {
+            fields.extend(encode_field(b'b', Some(pressure.min(99_999) as i32), 5));
+        }
+        match self.luminosity {
+            Some(lum) if lum >= 1000 => fields.extend(encode_field(b'l', Some(i32::from(lum) - 1000), 3)),
+            Some(lum) => fields.extend(encode_field(b'L', Some(i32::from(lum)), 3)),
+            None => {},
+        }
+        if let Some(snow) = self.snow {
+            // Tenths of an inch fit below 10 inches
+            let snow: f32 = snow.clamp(0.0, 999.0);
+            fields.push(b's');
+            if snow < 10.0 && snow.fract() != 0.0 {
+                fields.extend(format!("{:.1}", snow).as_bytes());
+            } else {
+                fields.extend(format!("{:03}", snow.round() as i32).as_bytes());
+            }
+        }
+        if self.rain_raw.is_some() {
+            fields.extend(encode_field(b'#', self.rain_raw.map(i32::from), 3));
+        }
+        fields
+    }
+}
+
+/// Parse a `_` positionless weather information field.
+pub fn parse_weather(raw_data: &[u8]) -> Result<Weather, ParseError> {
+    match raw_data.first() {
+        Some(&b'_') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    let mut weather: Weather = Weather::new();
+    match Timestamp::parse(&raw_data[1..]) {
+        Ok(timestamp @ Timestamp::Mdhm { .. }) => weather.timestamp = Some(timestamp),
+        Ok(_) => return Err(ParseError::InfoField { offset: 7 }),
+        Err(e) => return Err(e.shift(1)),
+    }
+
+    let mut pos: usize = 9;
+    pos += parse_wind(&raw_data[pos..], &mut weather);
+    pos += parse_fields(&raw_data[pos..], &mut weather);
+    weather.comment = raw_data[pos..].to_vec();
+    Ok(weather)
+}
+
+/// Parse a `#W1` or `*` Peet Bros raw weather information field.
+pub fn parse_peet_bros(raw_data: &[u8]) -> Result<Weather, ParseError> {
+    let (start, kph): (usize, bool) = match raw_data.first() {
+        Some(&b'#') if raw_data.starts_with(b"#W1") => (3, false),
+        Some(&b'#') => return Err(ParseError::InfoField { offset: 1 }),
+        Some(&b'*') => (1, true),
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    };
+    let mut weather: Weather = Weather::new();
+    let mut pos: usize = start;
+    for &(ind, width) in &[(0, 2), (1, 4), (2, 4), (3, 4)] {
+        // Later fields are optional
+        if raw_data.len() < pos + width && ind > 0 {
+            break;
+        }
+        let value: Option<u16> = parse_hex(&raw_data[pos..], width)
+            .map_err(|offset| ParseError::InfoField { offset: pos + offset })?;
+        match ind {
+            0 => weather.wind_direction = value.map(direction_degrees),
+            1 if kph => weather.wind_speed = value.map(kph_tenths_to_mph),
+            1 => weather.wind_speed = value.map(|speed| (f32::from(speed) / 10.0).round() as u16),
+            2 => weather.temperature = value.map(fahrenheit_tenths),
+            _ => weather.rain_raw = value,
+        }
+        pos += width;
+    }
+    weather.comment = raw_data[pos..].to_vec();
+    Ok(weather)
+}
+
+/// Parse a `$ULTW` Ultimeter packet mode or `!!` logging mode information field.
+pub fn parse_ultimeter(raw_data: &[u8]) -> Result<Weather, ParseError> {
+    match raw_data.first() {
+        Some(&b'$') | Some(&b'!') => {},
+        Some(byt) => return Err(ParseError::DataType { offset: 0, identifier: *byt }),
+        None => return Err(ParseError::InfoField { offset: 0 }),
+    }
+    if raw_data.starts_with(b"!!") {
+        return parse_ultimeter_logging(raw_data);
+    }
+    if !raw_data.starts_with(b"$ULTW") {
+        return Err(ParseError::InfoField { offset: 1 });
+    }
+    let (fields, pos): (Vec<Option<u16>>, usize) = parse_hex_fields(raw_data, 5, 13)?;
+    let mut weather: Weather = Weather::new();
+    weather.wind_gust = fields[0].map(kph_tenths_to_mph);
+    weather.wind_direction = fields[1].map(direction_degrees);
+    weather.temperature = fields[2].map(fahrenheit_tenths);
+    weather.rain_raw = fields[3];
+    weather.pressure = fields[4].map(u32::from);
+    weather.humidity = fields[8].map(|humidity| (f32::from(humidity) / 10.0).round().min(100.0) as u8);
+    weather.rain_midnight = fields[11];
+    weather.wind_speed = fields[12].map(kph_tenths_to_mph);
+    weather.comment = raw_data[pos..].to_vec();
+    Ok(weather)
+}
+
+// Logging mode gives the current wind speed first and its one-minute average last; the average
+// is kept when the station sends it.
+fn parse_ultimeter_logging(raw_data: &[u8]) -> Result<Weather, ParseError> {
+    let (fields, pos): (Vec<Option<u16>>, usize) = parse_hex_fields(raw_data, 2, 12)?;
+    let mut weather: Weather = Weather::new();
+    weather.wind_speed = fields[11].or(fields[0]).map(kph_tenths_to_mph);
+    weather.wind_direction = fields[1].map(direction_degrees);
+    weather.temperature = fields[2].map(fahrenheit_tenths);
+    weather.rain_raw = fields[3];
+    weather.pressure = fields[4].map(u32::from);
+    weather.humidity = fields[6].map(|humidity| (f32::from(humidity) / 10.0).round().min(100.0) as u8);
+    weather.rain_midnight = fields[10];
+    weather.comment = raw_data[pos..].to_vec();
+    Ok(weather)
+}
+
+// Four-digit hex fields from `start`, at least `min` of them. Returns the fields and the offset
+// after the last one.
+fn parse_hex_fields(raw_data: &[u8], start: usize, min: usize)
+    -> Result<(Vec<Option<u16>>, usize), ParseError> {
+    let mut fields: Vec<Option<u16>> = Vec::new();
+    let mut pos: usize = start;
+    while raw_data.len() >= pos + 4 {
+        match parse_hex(&raw_data[pos..], 4) {
+            Ok(value) => fields.push(value),
+            Err(_) if fields.len() >= min => break,
+            Err(offset) => return Err(ParseError::InfoField { offset: pos + offset }),
+        }
+        pos += 4;
+    }
+    if fields.len() < min {
+        return Err(ParseError::InfoField { offset: raw_data.len() });
+    }
+    Ok((fields, pos))
+}
+
+// Parse `width` hex digits; dashes mean no data. On error returns the offset of the bad digit.
+fn parse_hex(data: &[u8], width: usize) -> Result<Option<u16>, usize> {
+    if data.len() < width {
+        return Err(data.len());
+    }
+    let field: &[u8] = &data[..width];
+    if field.iter().all(|byt| *byt == b'-') {
+        return Ok(None);
+    }
+    if let Some(pos) = field.iter().position(|byt| !byt.is_ascii_hexdigit()) {
+        return Err(pos);
+    }
+    str::from_utf8(field).ok()
+        .and_then(|field| u16::from_str_radix(field, 16).ok())
+        .map(Some)
+        .ok_or(0)
+}
+
+// Raw wind directions divide the circle into 256 steps.
+fn direction_degrees(raw: u16) -> u16 {
+    ((f32::from(raw) * 360.0 / 256.0).round() as u16) % 360
+}
+
+fn kph_tenths_to_mph(raw: u16) -> u16 {
+    (f32::from(raw) / 10.0 * 0.621_371).round() as u16
+}
+
+// Temperatures are signed 16-bit tenths of a degree.
+fn fahrenheit_tenths(raw: u16) -> i16 {
+    (f32::from(raw as i16) / 10.0).round() as i16
+}
+
+/// Move the weather fields of a position report with the weather symbol to its `weather` field.
+///
+/// The wind of a compressed position is taken from its course and speed, which are cleared.
+pub(crate) fn extract_position_weather(position: &mut PositionFrame) {
+    if position.symbol != b'_' {
+        return;
+    }
+    let mut weather: Weather = Weather::new();
+    let comment: Vec<u8> = position.comment.clone();
+    let mut pos: usize = 0;
+
+    if position.compressed {
+        weather.wind_direction = position.course;
+        weather.wind_speed = position.speed.map(|speed| (speed / KNOTS_PER_MPH).round() as u16);
+    } else if comment.len() >= 7 && comment[3] == b'/' {
+        let dir: Option<Option<f32>> = parse_value(&comment[..3]);
+        let speed: Option<Option<f32>> = parse_value(&comment[4..7]);
+        if let (Some(dir), Some(speed)) = (dir, speed) {
+            weather.wind_direction = dir.map(|dir| dir as u16);
+            weather.wind_speed = speed.map(|speed| speed as u16);
+            pos = 7;
+        }
+    } else {
+        pos = parse_wind(&comment, &mut weather);
+    }
+    pos += parse_fields(&comment[pos..], &mut weather);
+    if pos == 0 && !position.compressed {
+        return;
+    }
+    if position.compressed {
+        position.course = None;
+        position.speed = None;
+    }
+    position.comment = comment[pos..].to_vec();
+    position.weather = Some(weather);
+}
+
+// Parse the `cDDDsSSS` wind fields and return the number of bytes used.
+fn parse_wind(data: &[u8], weather: &mut Weather) -> usize {
+    let mut pos: usize = 0;
+    if data.len() >= 4 && data[0] == b'c' {
+        if let Some(dir) = parse_value(&data[1..4]) {
+            weather.wind_direction = dir.map(|dir| dir as u16);
+            pos = 4;
+        }
+    }
+    if data.len() >= pos + 4 && data[pos] == b's' {
+        if let Some(speed) = parse_value(&data[(pos + 1)..(pos + 4)]) {
+            weather.wind_speed = speed.map(|speed| speed as u16);
+            pos += 4;
+        }
+    }
+    pos
+}
+
+// Parse letter-prefixed fields until one is not recognised and return the number of bytes used.
+fn parse_fields(data: &[u8], weather: &mut Weather) -> usize {
+    let mut pos: usize = 0;
+    while let Some(letter) = data.get(pos).cloned() {
+        let width: usize = match letter {
+            b'h' => 2,
+            b'b' => 5,
+            b'g' | b't' | b'r' | b'p' | b'P' | b'L' | b'l' | b's' | b'#' => 3,
+            _ => break,
+        };
+        if data.len() < pos + 1 + width {
+            break;
+        }
+        let value: Option<f32> = match parse_value(&data[(pos + 1)..(pos + 1 + width)]) {
+            Some(value) => value,
+            None => break,
+        };
+        match letter {
+            b'g' => weather.wind_gust = value.map(|val| val as u16),
+            b't' => weather.temperature = value.map(|val| val as i16),
+            b'r' => weather.rain_1h = value.map(|val| val as u16),
+            b'p' => weather.rain_24h = value.map(|val| val as u16),
+            b'P' => weather.rain_midnight = value.map(|val| val as u16),
+            b'h' => weather.humidity = value.map(|val| if val == 0.0 { 100 } else { val as u8 }),
+            b'b' => weather.pressure = value.map(|val| val as u32),
+            b'L' => weather.luminosity = value.map(|val| val as u16),
+            b'l' => weather.luminosity = value.map(|val| val as u16 + 1000),
+            b's' => weather.snow = value,
+            _ => weather.rain_raw = value.map(|val| val as u16),
+        }
+        pos += 1 + width;
+    }
+    pos
+}
+
+// `Some(None)` for an unknown value sent as dots or spaces, `None` if the field is malformed.
+fn parse_value(field: &[u8]) -> Option<Option<f32>> {
+    if field.iter().all(|byt| *byt == b'.' || *byt == b' ') {
+        return Some(None);
+    }
+    if !field.iter().all(|byt| byt.is_ascii_digit() || *byt == b'-' || *byt == b'.' || *byt == b' ') {
+        return None;
+    }
+    str::from_utf8(field).ok()?.trim().parse().ok().map(Some)
+}
+
+fn encode_field(letter: u8, value: Option<i32>, width: usize) -> Vec<u8> {
+    let mut encoded: Vec<u8> = vec![letter];
+    match value {
+        Some(value) => {
+            let max: i32 = 10_i32.pow(width as u32) - 1;
+            let min: i32 = -(10_i32.pow(width as u32 - 1) - 1);
+            encoded.extend(format!("{:0width$}", value.clamp(min, max), width = width).as_bytes());
+        },
+        None => encoded.extend(vec![b'.'; width]),
+    }
+    encoded
+}
+
+/// Wind speed in knots for the course and speed bytes of a compressed position.
+pub(crate) fn wind_speed_knots(weather: &Weather) -> Option<f32> {
+    weather.wind_speed.map(|speed| f32::from(speed) * KNOTS_PER_MPH)
+}
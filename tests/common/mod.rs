@@ -0,0 +1,25 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::fmt::Debug;
+
+use aprs::errors::{ParseError, EncodeError};
+
+
+/// Assert the error each input gives when parsed.
+pub fn assert_parse_errors<T, P>(parse: P, cases: &[(&[u8], ParseError)])
+    where T: Debug, P: Fn(&[u8]) -> Result<T, ParseError> {
+    for &(raw, ref error) in cases {
+        assert_eq!(parse(raw).err().as_ref(), Some(error), "{}", String::from_utf8_lossy(raw));
+    }
+}
+
+/// Assert that each information field parses and encodes back to the same bytes.
+pub fn assert_round_trip<T, P, C>(parse: P, create: C, raws: &[&[u8]])
+    where P: Fn(&[u8]) -> Result<T, ParseError>, C: Fn(&T) -> Result<Vec<u8>, EncodeError> {
+    for raw in raws {
+        let decoded: T = parse(raw).unwrap();
+        assert_eq!(create(&decoded).unwrap(), raw.to_vec(), "{}", String::from_utf8_lossy(raw));
+    }
+}
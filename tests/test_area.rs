@@ -0,0 +1,171 @@
+extern crate aprs;
+
+use aprs::area::{Area, Colour, Shape};
+use aprs::errors::EncodeError;
+use aprs::functions;
+use aprs::object::{self, Item, Object};
+use aprs::timestamp::Timestamp;
+
+#[test]
+fn test_decode_shapes() {
+    let shape = |comment: &[u8]| -> (Shape, bool) {
+        let (area, _) = Area::decode(comment).unwrap();
+        (area.shape, area.filled)
+    };
+    assert_eq!(shape(b"010/110"), (Shape::Circle, false));
+    assert_eq!(shape(b"110/110"), (Shape::LineRight, false));
+    assert_eq!(shape(b"610/110"), (Shape::LineLeft, false));
+    assert_eq!(shape(b"710/110"), (Shape::Ellipse, true));
+    assert_eq!(shape(b"810/110"), (Shape::Triangle, true));
+    assert_eq!(shape(b"410/110"), (Shape::Box, false));
+    assert_eq!(Area::decode(b"41x/110"), None);
+    assert_eq!(Area::decode(b"410-110"), None);
+}
+
+#[test]
+fn test_decode_colours_and_width() {
+    let (area, len) = Area::decode(b"405/702").unwrap();
+    assert_eq!(len, 7);
+    assert_eq!(area.colour, Colour::Grey);
+    assert!(area.bright);
+    assert_eq!(area.lat_offset, 0.25);
+    assert_eq!(area.lng_offset, 0.04);
+
+    let (area, len) = Area::decode(b"4051502 rest").unwrap();
+    assert_eq!(len, 7);
+    assert_eq!(area.colour, Colour::Grey);
+    assert!(!area.bright);
+    assert_eq!(Area::decode(b"4051602"), None);
+
+    // A digit after a one-digit colour belongs to the comment
+    let (area, len) = Area::decode(b"412/1225X").unwrap();
+    assert_eq!(len, 7);
+    assert_eq!(area.colour, Colour::Blue);
+    assert_eq!(area.lng_offset, 4.84);
+
+    let (area, len) = Area::decode(b"120/320{15}Route").unwrap();
+    assert_eq!(len, 11);
+    assert_eq!(area.width, Some(15));
+    let (area, len) = Area::decode(b"420/320{15}").unwrap();
+    assert_eq!(len, 7);
+    assert_eq!(area.width, None);
+}
+
+#[test]
+fn test_parse_area_object() {
+    let object: Object = object::parse_object(b";SEARCH 1 *092345z4903.50N\\07201.75Wl300/112Sector").unwrap();
+    let area: Area = object.position.area.unwrap();
+    assert_eq!(area.shape, Shape::Triangle);
+    assert_eq!(area.colour, Colour::Blue);
+    assert_eq!(area.lng_offset, 1.44);
+    assert_eq!(object.position.course, None);
+    assert_eq!(object.position.comment, b"Sector".to_vec());
+
+    // Other symbols keep the course and speed
+    let object: Object = object::parse_object(b";SEARCH 1 *092345z4903.50N\\07201.75Wk300/112Sector").unwrap();
+    assert_eq!(object.position.area, None);
+    assert_eq!(object.position.course, Some(300));
+}
+
+#[test]
+fn test_parse_signpost() {
+    let object: Object = object::parse_object(b";I-95 MM  *092345z4903.50N\\07201.75Wm{55}Speed limit").unwrap();
+    assert_eq!(object.position.signpost, Some("55".to_string()));
+    assert_eq!(object.position.comment, b"Speed limit".to_vec());
+
+    let object: Object = object::parse_object(b";I-95 MM  *092345z4903.50N\\07201.75Wm{}Sign").unwrap();
+    assert_eq!(object.position.signpost, None);
+    assert_eq!(object.position.comment, b"{}Sign".to_vec());
+
+    let item: Item = object::parse_item(b")EXIT4!4903.50N\\07201.75Wm{4A}").unwrap();
+    assert_eq!(item.position.signpost, Some("4A".to_string()));
+    let position = functions::parse_position(b"!4903.50N/07201.75Wm{55}").unwrap();
+    assert_eq!(position.signpost, None);
+}
+
+fn area_object(area: Area) -> Object {
+    let mut object: Object = Object::new();
+    object.name = "SEARCH 1".to_string();
+    object.timestamp = Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 };
+    object.position.lat = 49.058_334;
+    object.position.lng = -72.029_17;
+    object.position.table = b"\\".to_vec();
+    object.position.symbol = b'l';
+    object.position.area = Some(area);
+    object.position.comment = b"Sector".to_vec();
+    object
+}
+
+#[test]
+fn test_create_area_object() {
+    let mut area: Area = Area::new();
+    area.shape = Shape::Ellipse;
+    area.filled = true;
+    area.colour = Colour::Green;
+    area.bright = false;
+    area.lat_offset = 0.5;
+    area.lng_offset = 1.0;
+    let object: Object = area_object(area);
+    let info: Vec<u8> = object.create_info_field().unwrap();
+    assert_eq!(info, b";SEARCH 1 *092345z4903.50N\\07201.75Wl7071010Sector".to_vec());
+    let decoded: Object = object::parse_object(&info).unwrap();
+    assert_eq!(decoded.position.area.unwrap().shape, Shape::Ellipse);
+    assert_eq!(decoded.position.area.unwrap().lat_offset, 0.49);
+    assert_eq!(decoded.position.area.unwrap().lng_offset, 1.0);
+
+    let mut line: Area = Area::new();
+    line.shape = Shape::LineLeft;
+    line.lat_offset = 0.01;
+    line.width = Some(2);
+    let info: Vec<u8> = area_object(line).create_info_field().unwrap();
+    assert_eq!(info, b";SEARCH 1 *092345z4903.50N\\07201.75Wl601/400{2}Sector".to_vec());
+    assert_eq!(object::parse_object(&info).unwrap().position.area, Some(line));
+}
+
+#[test]
+fn test_create_signpost_item() {
+    let mut item: Item = Item::new();
+    item.name = "EXIT4".to_string();
+    item.position.lat = 49.058_334;
+    item.position.lng = -72.029_17;
+    item.position.table = b"\\".to_vec();
+    item.position.symbol = b'm';
+    item.position.signpost = Some("4A".to_string());
+    item.position.altitude = Some(120.0);
+    let info: Vec<u8> = item.create_info_field().unwrap();
+    assert_eq!(info, b")EXIT4!4903.50N\\07201.75Wm/A=000120{4A}".to_vec());
+    let decoded: Item = object::parse_item(&info).unwrap();
+    assert_eq!(decoded.position.signpost, Some("4A".to_string()));
+    assert_eq!(decoded.position.altitude, Some(120.0));
+    assert!(decoded.position.comment.is_empty());
+
+    item.position.signpost = Some("1234".to_string());
+    assert_eq!(item.create_info_field(), Err(EncodeError::TooLong { field: "signpost", max: 3 }));
+    item.position.signpost = Some("{".to_string());
+    assert_eq!(item.create_info_field(), Err(EncodeError::InvalidCharacter { field: "signpost", offset: 0 }));
+    item.position.signpost = Some("55".to_string());
+    item.position.symbol = b'l';
+    assert_eq!(item.create_info_field(), Err(EncodeError::Conflict { field: "signpost", other: "symbol" }));
+}
+
+#[test]
+fn test_create_area_errors() {
+    let mut area: Area = Area::new();
+    area.shape = Shape::LineRight;
+    area.filled = true;
+    assert_eq!(area_object(area).create_info_field(),
+               Err(EncodeError::Conflict { field: "filled", other: "shape" }));
+
+    let mut area: Area = Area::new();
+    area.width = Some(3);
+    assert_eq!(area_object(area).create_info_field(),
+               Err(EncodeError::Conflict { field: "width", other: "shape" }));
+
+    let mut area: Area = Area::new();
+    area.lng_offset = 100.0;
+    assert_eq!(area_object(area).create_info_field(), Err(EncodeError::OutOfRange { field: "lng offset" }));
+
+    let mut object: Object = area_object(Area::new());
+    object.position.symbol = b'k';
+    assert_eq!(object.create_info_field(), Err(EncodeError::Conflict { field: "area", other: "symbol" }));
+}
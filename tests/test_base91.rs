@@ -0,0 +1,24 @@
+extern crate aprs;
+use aprs::base91;
+
+#[test]
+fn test_encode_decode() {
+    for value in &[0_u32, 1, 90, 91, 8280, 753_571, 68_574_960] {
+        let encoded: Vec<u8> = base91::encode(*value, 4).unwrap();
+        assert_eq!(encoded.len(), 4);
+        assert_eq!(base91::decode(&encoded), Some(*value));
+    }
+}
+
+#[test]
+fn test_encode_overflow() {
+    assert_eq!(base91::encode(91, 1), None);
+    assert_eq!(base91::encode(90, 1), Some(b"{".to_vec()));
+}
+
+#[test]
+fn test_decode_invalid() {
+    assert_eq!(base91::decode(b" !"), None);
+    assert_eq!(base91::decode(b"|"), None);
+    assert_eq!(base91::decode(b"{{{{{{"), None);
+}
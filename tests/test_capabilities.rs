@@ -0,0 +1,82 @@
+extern crate aprs;
+
+use aprs::capabilities::{Capabilities, Token, parse_capabilities};
+use aprs::errors::{ParseError, EncodeError};
+use aprs::functions;
+use aprs::payload::Payload;
+
+mod common;
+use common::assert_parse_errors;
+
+#[test]
+fn test_parse_igate_capabilities() {
+    let capabilities: Capabilities = parse_capabilities(b"<IGATE,MSG_CNT=43,LOC_CNT=14\r\n").unwrap();
+    assert_eq!(capabilities.tokens, vec![
+        Token { key: "IGATE".to_string(), value: None },
+        Token { key: "MSG_CNT".to_string(), value: Some("43".to_string()) },
+        Token { key: "LOC_CNT".to_string(), value: Some("14".to_string()) },
+    ]);
+    assert!(capabilities.is_igate());
+    assert_eq!(capabilities.message_count(), Some(43));
+    assert_eq!(capabilities.local_count(), Some(14));
+}
+
+#[test]
+fn test_parse_other_capabilities() {
+    let capabilities: Capabilities = parse_capabilities(b"<DIGI, WX=1 ,MSG_CNT=many,").unwrap();
+    assert_eq!(capabilities.tokens.len(), 3);
+    assert!(!capabilities.is_igate());
+    assert!(capabilities.has("digi"));
+    assert_eq!(capabilities.get("WX"), Some("1"));
+    assert_eq!(capabilities.get("DIGI"), None);
+    assert_eq!(capabilities.message_count(), None);
+    assert_eq!(capabilities.local_count(), None);
+}
+
+#[test]
+fn test_parse_capabilities_errors() {
+    assert_parse_errors(parse_capabilities, &[
+        (b"<IGATE,=4", ParseError::InfoField { offset: 7 }),
+        (b">IGATE", ParseError::DataType { offset: 0, identifier: b'>' }),
+        (b"", ParseError::InfoField { offset: 0 }),
+    ]);
+}
+
+#[test]
+fn test_capabilities_payload() {
+    let frame = functions::parse_frame(b"N0CALL>APRS:<IGATE,MSG_CNT=2,LOC_CNT=5").unwrap();
+    match Payload::from_frame(&frame).unwrap() {
+        Payload::Capabilities(capabilities) => assert_eq!(capabilities.local_count(), Some(5)),
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+}
+
+#[test]
+fn test_create_capabilities() {
+    let mut capabilities: Capabilities = Capabilities::igate(0, 3);
+    assert_eq!(capabilities.create_info_field().unwrap(), b"<IGATE,MSG_CNT=0,LOC_CNT=3".to_vec());
+    capabilities.set("MSG_CNT", Some("7"));
+    capabilities.set("WX", Some("1"));
+    assert_eq!(capabilities.create_info_field().unwrap(), b"<IGATE,MSG_CNT=7,LOC_CNT=3,WX=1".to_vec());
+
+    let info: Vec<u8> = capabilities.create_info_field().unwrap();
+    assert_eq!(parse_capabilities(&info).unwrap(), capabilities);
+}
+
+#[test]
+fn test_create_capabilities_errors() {
+    let mut capabilities: Capabilities = Capabilities::new();
+    assert_eq!(capabilities.create_info_field(), Err(EncodeError::Missing { field: "tokens" }));
+    capabilities.set("", None);
+    assert_eq!(capabilities.create_info_field(), Err(EncodeError::TooShort { field: "key", min: 1 }));
+
+    let mut capabilities: Capabilities = Capabilities::new();
+    capabilities.set("A=B", None);
+    assert_eq!(capabilities.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "key", offset: 1 }));
+
+    let mut capabilities: Capabilities = Capabilities::new();
+    capabilities.set("NAME", Some("a,b"));
+    assert_eq!(capabilities.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "value", offset: 1 }));
+}
@@ -0,0 +1,155 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::extension::{Phg, Dfs, Dao};
+use aprs::structs::PositionFrame;
+
+#[test]
+fn test_parse_course_speed() {
+    let position = functions::parse_position(b"!4903.50N/07201.75W>088/036/A=001234Moving").unwrap();
+    assert_eq!(position.course, Some(88));
+    assert_eq!(position.speed, Some(36.0));
+    assert_eq!(position.altitude, Some(1234.0));
+    assert_eq!(position.comment, b"Moving".to_vec());
+
+    let position = functions::parse_position(b"!4903.50N/07201.75W>360/.../A=-00012").unwrap();
+    assert_eq!(position.course, Some(0));
+    assert_eq!(position.speed, None);
+    assert_eq!(position.altitude, Some(-12.0));
+    assert!(position.comment.is_empty());
+
+    let position = functions::parse_position(b"!4903.50N/07201.75W>000/010").unwrap();
+    assert_eq!(position.course, None);
+    assert_eq!(position.speed, Some(10.0));
+
+    // Not an extension: left in the comment
+    let position = functions::parse_position(b"!4903.50N/07201.75W>400/010 /A=12").unwrap();
+    assert_eq!(position.course, None);
+    assert_eq!(position.altitude, None);
+    assert_eq!(position.comment, b"400/010 /A=12".to_vec());
+}
+
+#[test]
+fn test_parse_phg_rng_dfs() {
+    let position = functions::parse_position(b"=4903.50N/07201.75W#PHG7260W2, digi").unwrap();
+    assert_eq!(position.phg, Some(Phg { power: 49, height: 40, gain: 6, directivity: None }));
+    assert_eq!(position.comment, b"W2, digi".to_vec());
+    assert!((position.phg.unwrap().range() - 15.81).abs() < 0.01);
+
+    let position = functions::parse_position(b"=4903.50N/07201.75W#PHG5;38").unwrap();
+    assert_eq!(position.phg, Some(Phg { power: 25, height: 20_480, gain: 3, directivity: Some(0) }));
+
+    let position = functions::parse_position(b"=4903.50N/07201.75W#RNG0050").unwrap();
+    assert_eq!(position.range, Some(50.0));
+    assert!(position.comment.is_empty());
+
+    let position = functions::parse_position(b"=4903.50N/07201.75W\\DFS2360").unwrap();
+    assert_eq!(position.dfs, Some(Dfs { strength: 2, height: 80, gain: 6, directivity: None }));
+
+    let position = functions::parse_position(b"=4903.50N/07201.75W#PHG729").unwrap();
+    assert_eq!(position.phg, None);
+    assert_eq!(position.comment, b"PHG729".to_vec());
+}
+
+#[test]
+fn test_encode_extensions() {
+    let mut position = PositionFrame::new();
+    position.messaging = false;
+    position.table = b"/".to_vec();
+    position.symbol = b'>';
+    position.lat = 49.058_334;
+    position.lng = -72.029_17;
+    position.course = Some(0);
+    position.speed = Some(35.6);
+    position.altitude = Some(1234.4);
+    position.comment = b"Moving".to_vec();
+    let info = position.create_info_field();
+    assert_eq!(info, b"!4903.50N/07201.75W>360/036/A=001234Moving".to_vec());
+    assert_eq!(functions::parse_position(&info).unwrap().course, Some(0));
+
+    position.course = None;
+    position.speed = None;
+    position.altitude = None;
+    position.comment = Vec::new();
+    position.phg = Some(Phg { power: 50, height: 80, gain: 3, directivity: Some(90) });
+    assert_eq!(position.create_info_field(), b"!4903.50N/07201.75W>PHG7332".to_vec());
+
+    position.phg = None;
+    position.range = Some(12.4);
+    assert_eq!(position.create_info_field(), b"!4903.50N/07201.75W>RNG0012".to_vec());
+
+    position.range = None;
+    position.dfs = Some(Dfs { strength: 4, height: 10, gain: 0, directivity: Some(180) });
+    assert_eq!(position.create_info_field(), b"!4903.50N/07201.75W>DFS4004".to_vec());
+}
+
+#[test]
+fn test_encode_compressed_altitude() {
+    let mut position = PositionFrame::new();
+    position.compressed = true;
+    position.table = b"/".to_vec();
+    position.symbol = b'>';
+    position.lat = 49.5;
+    position.lng = -72.75;
+    position.altitude = Some(10_004.0);
+    let parsed = functions::parse_position(&position.create_info_field()).unwrap();
+    assert!((parsed.altitude.unwrap() - 10_004.0).abs() < 20.0);
+    assert!(parsed.comment.is_empty());
+
+    // With course and speed in the compressed bytes, the altitude goes in the comment
+    position.course = Some(88);
+    position.speed = Some(36.0);
+    let parsed = functions::parse_position(&position.create_info_field()).unwrap();
+    assert_eq!(parsed.altitude, Some(10_004.0));
+    assert_eq!(parsed.course, Some(88));
+    assert!(parsed.comment.is_empty());
+}
+
+#[test]
+fn test_parse_dao() {
+    let position = functions::parse_position(b"!4903.50N/07201.75W-Home !W27!").unwrap();
+    assert_eq!(position.dao, Some(Dao::HumanReadable));
+    assert!((position.lat - (49.0 + 3.502 / 60.0)).abs() < 1e-5);
+    assert!((position.lng + (72.0 + 1.757 / 60.0)).abs() < 1e-5);
+    assert_eq!(position.comment, b"Home ".to_vec());
+
+    let position = functions::parse_position(b"!4903.50S/07201.75E-!wY;!/A=000100").unwrap();
+    assert_eq!(position.dao, Some(Dao::Base91));
+    assert!((position.lat + (49.0 + (3.50 + 0.01 * 56.0 / 91.0) / 60.0)).abs() < 1e-5);
+    assert!((position.lng - (72.0 + (1.75 + 0.01 * 26.0 / 91.0) / 60.0)).abs() < 1e-5);
+    assert_eq!(position.altitude, Some(100.0));
+    assert!(position.comment.is_empty());
+
+    // Ambiguous positions keep the centre of their area
+    let position = functions::parse_position(b"!4903.5 N/07201.7 W-!W27!").unwrap();
+    assert_eq!(position.dao, Some(Dao::HumanReadable));
+    assert!((position.lat - (49.0 + 3.55 / 60.0)).abs() < 1e-5);
+
+    let position = functions::parse_position(b"!4903.50N/07201.75W-!X27! !W2x!").unwrap();
+    assert_eq!(position.dao, None);
+    assert_eq!(position.comment, b"!X27! !W2x!".to_vec());
+}
+
+#[test]
+fn test_encode_dao() {
+    let mut position = PositionFrame::new();
+    position.messaging = false;
+    position.table = b"/".to_vec();
+    position.symbol = b'-';
+    position.lat = 49.0 + 3.506 / 60.0;
+    position.lng = -(72.0 + 1.759 / 60.0);
+    position.comment = b"Home".to_vec();
+    position.dao = Some(Dao::HumanReadable);
+    assert_eq!(position.create_info_field(), b"!4903.50N/07201.75W-Home!W69!".to_vec());
+
+    position.dao = Some(Dao::Base91);
+    let info = position.create_info_field();
+    assert_eq!(&info[..20], b"!4903.50N/07201.75W-");
+    let parsed = functions::parse_position(&info).unwrap();
+    assert_eq!(parsed.dao, Some(Dao::Base91));
+    assert_eq!(parsed.comment, b"Home".to_vec());
+    assert!((parsed.lat - position.lat).abs() < 1e-5);
+    assert!((parsed.lng - position.lng).abs() < 1e-5);
+
+    position.ambiguity = 1;
+    assert_eq!(position.create_info_field(), b"!4903.5 N/07201.7 W-Home".to_vec());
+}
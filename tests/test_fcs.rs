@@ -7,13 +7,38 @@ use data_encoding::HEXLOWER as HEX;
 #[path="../tests/test_constants.rs"]
 mod test_constants;
 
+fn to_bits(bytes: &[u8]) -> Vec<bool> {
+    // AX.25 sends the least significant bit of each byte first
+    let mut bits: Vec<bool> = Vec::new();
+    for byte in bytes {
+        for i in 0..8 {
+            bits.push((byte >> i) & 0x01 == 1);
+        }
+    }
+    bits
+}
+
 #[test]
-#[ignore]
 fn test_fcs_checksum() {
+    let mut sum = fcs::FCS::new();
+    sum.update_bytes(b"123456789");
+    assert_eq!(sum.digest(), vec![0x6e, 0x90]);
+
+    let frame: Vec<u8> = HEX.decode(test_constants::TEST_HEX_FRAME.as_bytes()).unwrap();
+    let mut sum = fcs::FCS::new();
+    sum.update_bytes(&frame);
+    let mut bits: Vec<bool> = to_bits(&frame);
+    bits.extend(to_bits(&sum.digest()));
+    assert_eq!(fcs::fcs(to_bits(&frame)), bits);
 }
 
 
 #[test]
-#[ignore]
 fn test_fcs_validate() {
+    let frame: Vec<u8> = HEX.decode(test_constants::TEST_HEX_FRAME.as_bytes()).unwrap();
+    let mut bits: Vec<bool> = fcs::fcs(to_bits(&frame));
+    assert_eq!(fcs::fcs_validate(bits.clone()), Ok(true));
+
+    bits[3] = !bits[3];
+    assert_eq!(fcs::fcs_validate(bits), Err(String::from("Invalid FCS")));
 }
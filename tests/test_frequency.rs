@@ -0,0 +1,126 @@
+extern crate aprs;
+
+use aprs::errors::EncodeError;
+use aprs::frequency::{Frequency, Tone};
+use aprs::functions;
+use aprs::object::{self, Object};
+use aprs::structs::PositionFrame;
+use aprs::timestamp::Timestamp;
+
+#[test]
+fn test_decode_frequency() {
+    let (frequency, len) = Frequency::decode(b"146.520MHz T100 +060 Simplex").unwrap();
+    assert_eq!(len, 21);
+    assert_eq!(frequency.frequency, 146_520);
+    assert_eq!(frequency.tone, Some(Tone::Ctcss(100)));
+    assert!(!frequency.narrow);
+    assert_eq!(frequency.offset, Some(600));
+    assert_eq!(frequency.range, None);
+    assert!(frequency.extra.is_empty());
+
+    let (frequency, len) = Frequency::decode(b"1296.00MHz").unwrap();
+    assert_eq!(len, 10);
+    assert_eq!(frequency.frequency, 1_296_000);
+    assert_eq!(frequency.tone, None);
+
+    assert_eq!(Frequency::decode(b"146.52 MHz"), None);
+    assert_eq!(Frequency::decode(b"Net on 146.520MHz"), None);
+}
+
+#[test]
+fn test_decode_frequency_fields() {
+    let field = |comment: &[u8]| -> Frequency { Frequency::decode(comment).unwrap().0 };
+    assert_eq!(field(b"444.000MHz C131").tone, Some(Tone::ToneSquelch(131)));
+    assert_eq!(field(b"444.000MHz D023").tone, Some(Tone::Dcs(23)));
+    assert_eq!(field(b"145.600MHz 1750").tone, Some(Tone::Burst));
+    assert_eq!(field(b"145.600MHz Toff").tone, Some(Tone::Off));
+    let narrow: Frequency = field(b"446.100MHz t088 -500");
+    assert_eq!(narrow.tone, Some(Tone::Ctcss(88)));
+    assert!(narrow.narrow);
+    assert_eq!(narrow.offset, Some(-5000));
+
+    let range: Frequency = field(b"147.105MHz R40k F443.125 F145.500");
+    assert!((range.range.unwrap() - 24.85).abs() < 0.01);
+    assert_eq!(range.extra, vec![443_125, 145_500]);
+
+    // Repeated or unknown fields end the specification
+    let (frequency, len) = Frequency::decode(b"147.105MHz T100 T123 Hello").unwrap();
+    assert_eq!(frequency.tone, Some(Tone::Ctcss(100)));
+    assert_eq!(len, 16);
+    let (_, len) = Frequency::decode(b"147.105MHz Tx00").unwrap();
+    assert_eq!(len, 11);
+}
+
+#[test]
+fn test_parse_position_frequency() {
+    let position: PositionFrame = functions::parse_position(
+        b"!4903.50N/07201.75Wr360/000147.105MHz T100 +060 R25m /A=001234Club net").unwrap();
+    assert_eq!(position.course, Some(0));
+    assert_eq!(position.altitude, Some(1234.0));
+    let frequency: Frequency = position.frequency.unwrap();
+    assert_eq!(frequency.frequency, 147_105);
+    assert_eq!(frequency.range, Some(25.0));
+    assert_eq!(position.comment, b"Club net".to_vec());
+
+    let position: PositionFrame = functions::parse_position(b"!4903.50N/07201.75W-Home").unwrap();
+    assert_eq!(position.frequency, None);
+}
+
+#[test]
+fn test_encode_frequency() {
+    let mut frequency: Frequency = Frequency::new();
+    frequency.frequency = 146_940;
+    frequency.tone = Some(Tone::Dcs(23));
+    frequency.narrow = true;
+    frequency.offset = Some(-600);
+    frequency.range = Some(24.6);
+    frequency.extra = vec![443_125];
+    assert_eq!(frequency.encode(), b"146.940MHz d023 -060 R25m F443.125".to_vec());
+    assert_eq!(frequency.object_name("NE"), "146.940NE");
+
+    frequency.frequency = 1_296_125;
+    frequency.tone = None;
+    frequency.offset = None;
+    frequency.range = None;
+    frequency.extra = Vec::new();
+    assert_eq!(frequency.encode(), b"1296.12MHz".to_vec());
+}
+
+#[test]
+fn test_create_repeater_object() {
+    let mut frequency: Frequency = Frequency::new();
+    frequency.frequency = 147_105;
+    frequency.tone = Some(Tone::Ctcss(100));
+    frequency.offset = Some(600);
+    let mut object: Object = Object::repeater(frequency.clone(), "-C", 49.058_334, -72.029_17);
+    object.timestamp = Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 };
+    object.position.comment = b"Club".to_vec();
+
+    let info: Vec<u8> = object.create_info_field().unwrap();
+    assert_eq!(info, b";147.105-C*092345z4903.50N/07201.75Wr147.105MHz T100 +060 Club".to_vec());
+    let decoded: Object = object::parse_object(&info).unwrap();
+    assert_eq!(decoded.name, "147.105-C");
+    assert_eq!(decoded.position.frequency, Some(frequency));
+    assert_eq!(decoded.position.comment, b"Club".to_vec());
+}
+
+#[test]
+fn test_create_repeater_object_errors() {
+    let repeater = |frequency: Frequency| -> Result<Vec<u8>, EncodeError> {
+        Object::repeater(frequency, "", 49.0, -72.0).create_info_field()
+    };
+    assert_eq!(repeater(Frequency::new()), Err(EncodeError::Missing { field: "frequency" }));
+
+    let mut frequency: Frequency = Frequency::new();
+    frequency.frequency = 1_296_125;
+    assert_eq!(repeater(frequency.clone()), Err(EncodeError::OutOfRange { field: "frequency" }));
+    frequency.frequency = 146_520;
+    frequency.offset = Some(605);
+    assert_eq!(repeater(frequency.clone()), Err(EncodeError::OutOfRange { field: "offset" }));
+    frequency.offset = None;
+    frequency.tone = Some(Tone::Ctcss(1000));
+    assert_eq!(repeater(frequency.clone()), Err(EncodeError::OutOfRange { field: "tone" }));
+    frequency.tone = None;
+    frequency.extra = vec![1_240_000];
+    assert_eq!(repeater(frequency), Err(EncodeError::OutOfRange { field: "extra frequency" }));
+}
@@ -20,3 +20,11 @@ fn test_ambiguate() {
     assert_eq!(geo_util::ambiguate(&lat1, 3), "1222 .  W".to_string());
     assert_eq!(geo_util::ambiguate(&lon1, 1), "3746.4 N".to_string());
 }
+
+#[test]
+fn test_compress_round_trip() {
+    let lat: f32 = geo_util::decompress_lat(&geo_util::compress_lat(37.77397_f32)).unwrap();
+    let lng: f32 = geo_util::decompress_lng(&geo_util::compress_lng(-122.4313_f32)).unwrap();
+    assert!((lat - 37.77397_f32).abs() < 0.00001);
+    assert!((lng - -122.4313_f32).abs() < 0.00001);
+}
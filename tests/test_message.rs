@@ -0,0 +1,105 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::message::{Message, MessageKind, parse_message};
+use aprs::errors::{ParseError, EncodeError};
+
+mod common;
+use common::assert_parse_errors;
+
+#[test]
+fn test_parse_message() {
+    let frame = functions::parse_frame(b"W2GMD>APRS::KF4MKT-1 :Hello there{A12").unwrap();
+    let message = Message::from_frame(&frame).unwrap();
+    assert_eq!(message.addressee, "KF4MKT-1");
+    assert_eq!(message.kind, MessageKind::Message);
+    assert_eq!(message.text, "Hello there");
+    assert_eq!(message.id, Some("A12".to_string()));
+    assert_eq!(message.reply_ack, None);
+}
+
+#[test]
+fn test_parse_message_without_id() {
+    let message = parse_message(b":BLN1     :Net tonight at 8 {not an id}").unwrap();
+    assert_eq!(message.addressee, "BLN1");
+    assert_eq!(message.text, "Net tonight at 8 {not an id}");
+    assert_eq!(message.id, None);
+}
+
+#[test]
+fn test_parse_ack_rej() {
+    let message = parse_message(b":W2GMD    :ack003").unwrap();
+    assert_eq!(message.kind, MessageKind::Ack);
+    assert_eq!(message.id, Some("003".to_string()));
+    assert!(message.text.is_empty());
+
+    let message = parse_message(b":W2GMD    :rej42\r\n").unwrap();
+    assert_eq!(message.kind, MessageKind::Rej);
+    assert_eq!(message.id, Some("42".to_string()));
+}
+
+#[test]
+fn test_parse_reply_ack() {
+    let message = parse_message(b":W2GMD    :Roger that{MM}AA").unwrap();
+    assert_eq!(message.text, "Roger that");
+    assert_eq!(message.id, Some("MM".to_string()));
+    assert_eq!(message.reply_ack, Some("AA".to_string()));
+
+    let message = parse_message(b":W2GMD    :First message{01}").unwrap();
+    assert_eq!(message.id, Some("01".to_string()));
+    assert_eq!(message.reply_ack, Some(String::new()));
+}
+
+#[test]
+fn test_parse_message_errors() {
+    assert_parse_errors(parse_message, &[
+        (b":W2GMD:hi", ParseError::InfoField { offset: 9 }),
+        (b":W2GMD     hi", ParseError::InfoField { offset: 10 }),
+        (b":W2GMD    :h\xffi", ParseError::InfoField { offset: 12 }),
+    ]);
+}
+
+#[test]
+fn test_encode_message() {
+    let mut message = Message::new();
+    message.addressee = "KF4MKT-1".to_string();
+    message.text = "Hello there".to_string();
+    message.id = Some("7".to_string());
+    assert_eq!(message.create_info_field().unwrap(), b":KF4MKT-1 :Hello there{7".to_vec());
+
+    message.reply_ack = Some("AB".to_string());
+    let encoded: Vec<u8> = message.create_info_field().unwrap();
+    assert_eq!(encoded, b":KF4MKT-1 :Hello there{7}AB".to_vec());
+    assert_eq!(parse_message(&encoded).unwrap(), message);
+
+    assert_eq!(Message::ack("W2GMD", "003").create_info_field().unwrap(),
+               b":W2GMD    :ack003".to_vec());
+    assert_eq!(Message::rej("W2GMD", "42").create_info_field().unwrap(),
+               b":W2GMD    :rej42".to_vec());
+}
+
+#[test]
+fn test_encode_message_errors() {
+    let mut message = Message::new();
+    message.addressee = "KF4MKT-10X".to_string();
+    assert_eq!(message.create_info_field(),
+               Err(EncodeError::TooLong { field: "addressee", max: 9 }));
+
+    message.addressee = "W2GMD".to_string();
+    message.text = "x".repeat(68);
+    assert_eq!(message.create_info_field(), Err(EncodeError::TooLong { field: "text", max: 67 }));
+
+    message.text = "pipes | are reserved".to_string();
+    assert_eq!(message.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "text", offset: 6 }));
+
+    message.text = "ok".to_string();
+    message.id = Some("123456".to_string());
+    assert_eq!(message.create_info_field(),
+               Err(EncodeError::TooLong { field: "message number", max: 5 }));
+    message.id = Some(String::new());
+    assert_eq!(message.create_info_field(), Err(EncodeError::Missing { field: "message number" }));
+
+    let mut ack = Message::ack("W2GMD", "1");
+    ack.id = None;
+    assert_eq!(ack.create_info_field(), Err(EncodeError::Missing { field: "message number" }));
+}
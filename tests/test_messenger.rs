@@ -0,0 +1,153 @@
+extern crate aprs;
+use std::time::Duration;
+
+use aprs::clock::ManualClock;
+use aprs::functions;
+use aprs::message::{Message, MessageKind};
+use aprs::messenger::{Messenger, MessengerEvent};
+use aprs::structs::Frame;
+
+fn messenger(clock: &ManualClock) -> Messenger<&ManualClock> {
+    Messenger::new(functions::parse_callsign(b"W2GMD-6").unwrap(), clock)
+}
+
+fn frame(raw: &[u8]) -> Frame {
+    functions::parse_frame(raw).unwrap()
+}
+
+#[test]
+fn test_send_numbers_per_addressee() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    assert_eq!(messenger.send("KF4MKT", "one").unwrap(), "1");
+    assert_eq!(messenger.send("KF4MKT", "two").unwrap(), "2");
+    assert_eq!(messenger.send("N6GSO", "three").unwrap(), "1");
+
+    let frames: Vec<Frame> = messenger.poll();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].source.to_string(), "W2GMD-6");
+    assert_eq!(frames[0].info.data, b":KF4MKT   :one{1".to_vec());
+    assert!(messenger.send("KF4MKT", &"x".repeat(68)).is_err());
+}
+
+#[test]
+fn test_retry_schedule_and_timeout() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    messenger.set_max_attempts(3);
+    messenger.send("KF4MKT", "hello").unwrap();
+
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(29));
+    assert!(messenger.poll().is_empty());
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(30));
+    assert!(messenger.poll().is_empty());
+    clock.advance(Duration::from_secs(30));
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(120));
+    assert!(messenger.poll().is_empty());
+    assert_eq!(messenger.events(), vec![MessengerEvent::TimedOut {
+        addressee: "KF4MKT".to_string(),
+        id: "1".to_string(),
+    }]);
+    assert_eq!(messenger.pending(), 0);
+}
+
+#[test]
+fn test_retry_interval_limit() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    messenger.set_max_attempts(20);
+    messenger.set_max_retry_interval(Duration::from_secs(100));
+    messenger.send("KF4MKT", "hello").unwrap();
+
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(30));
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(messenger.poll().len(), 1);
+    // 120 seconds is cut to the limit
+    clock.advance(Duration::from_secs(100));
+    assert_eq!(messenger.poll().len(), 1);
+
+    // A retry interval too large to double waits the limit rather than panicking
+    messenger.set_retry_interval(Duration::new(u64::MAX, 0));
+    clock.advance(Duration::from_secs(100));
+    assert_eq!(messenger.poll().len(), 1);
+    clock.advance(Duration::from_secs(100));
+    assert_eq!(messenger.poll().len(), 1);
+}
+
+#[test]
+fn test_ack_and_rej() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    messenger.send("KF4MKT", "hello").unwrap();
+    messenger.send("KF4MKT", "again").unwrap();
+    messenger.poll();
+
+    assert!(messenger.receive(&frame(b"KF4MKT>APRS::W2GMD-6  :ack1")).is_empty());
+    messenger.receive(&frame(b"KF4MKT>APRS::W2GMD-6  :rej2"));
+    messenger.receive(&frame(b"N6GSO>APRS::W2GMD-6  :ack1"));
+    assert_eq!(messenger.events(), vec![
+        MessengerEvent::Delivered { addressee: "KF4MKT".to_string(), id: "1".to_string() },
+        MessengerEvent::Rejected { addressee: "KF4MKT".to_string(), id: "2".to_string() },
+    ]);
+    assert_eq!(messenger.pending(), 0);
+}
+
+#[test]
+fn test_reply_ack() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    messenger.send("KF4MKT", "hello").unwrap();
+    messenger.receive(&frame(b"KF4MKT>APRS::W2GMD-6  :hi back{7}1"));
+    let events: Vec<MessengerEvent> = messenger.events();
+    assert_eq!(events[0], MessengerEvent::Delivered {
+        addressee: "KF4MKT".to_string(),
+        id: "1".to_string(),
+    });
+    match events[1] {
+        MessengerEvent::Received { ref message, .. } => assert_eq!(message.text, "hi back"),
+        ref event => panic!("unexpected event {:?}", event),
+    }
+}
+
+#[test]
+fn test_receive_auto_ack_and_duplicates() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut messenger = messenger(&clock);
+    let incoming: Frame = frame(b"KF4MKT>APRS,WIDE1-1::W2GMD-6  :Hello{42");
+
+    let acks: Vec<Frame> = messenger.receive(&incoming);
+    assert_eq!(acks.len(), 1);
+    assert_eq!(acks[0].info.data, b":KF4MKT   :ack42".to_vec());
+    let ack: Message = Message::from_frame(&acks[0]).unwrap();
+    assert_eq!(ack.kind, MessageKind::Ack);
+
+    // The duplicate is acknowledged again but not reported
+    assert_eq!(messenger.receive(&incoming).len(), 1);
+    assert_eq!(messenger.events().len(), 1);
+
+    clock.advance(Duration::from_secs(31 * 60));
+    messenger.receive(&incoming);
+    assert_eq!(messenger.events().len(), 1);
+
+    // Messages for other stations are ignored
+    assert!(messenger.receive(&frame(b"KF4MKT>APRS::N6GSO    :Hello{43")).is_empty());
+    assert!(messenger.events().is_empty());
+}
+
+#[test]
+fn test_unbounded_duplicate_window() {
+    let clock = ManualClock::new(Duration::from_secs(1_000));
+    let mut messenger = messenger(&clock);
+    messenger.set_duplicate_window(Duration::MAX);
+    let incoming: Frame = frame(b"KF4MKT>APRS::W2GMD-6  :Hello{42");
+
+    messenger.receive(&incoming);
+    messenger.receive(&incoming);
+    assert_eq!(messenger.events().len(), 1);
+}
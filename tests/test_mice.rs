@@ -0,0 +1,127 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::structs::{Frame, PositionFrame};
+use aprs::mice::{MicE, MicEMessage};
+use aprs::errors::ParseError;
+
+#[test]
+fn test_decode_mice_kenwood() {
+    let frame = functions::parse_frame(b"W2GMD-9>S32U6T,WIDE1-1:`dINn\"O>/>Hello=").unwrap();
+    let mice = MicE::from_frame(&frame).unwrap();
+    assert!(mice.current);
+    assert_eq!(mice.message, MicEMessage::Returning);
+    assert_eq!(mice.radio, Some("Kenwood TH-D72"));
+
+    let position = mice.position;
+    assert_eq!(position.source.callsign, "W2GMD");
+    assert!((position.lat - 33.427_334_f32).abs() < 0.00001);
+    assert!((position.lng - -72.758_33_f32).abs() < 0.00001);
+    assert_eq!(position.speed, Some(20_f32));
+    assert_eq!(position.course, Some(251));
+    assert_eq!(position.symbol, b'>');
+    assert_eq!(position.table, b"/".to_vec());
+    assert_eq!(position.altitude, None);
+    assert_eq!(position.comment, b"Hello".to_vec());
+}
+
+#[test]
+fn test_decode_mice_altitude_suffix() {
+    let frame = functions::parse_frame(b"W2GMD-9>PPPPPP:`dINn\"O>/`\"4{}Hi_%").unwrap();
+    let mice = MicE::from_frame(&frame).unwrap();
+    assert_eq!(mice.message, MicEMessage::OffDuty);
+    assert_eq!(mice.radio, Some("Yaesu FTM-400DR"));
+    assert!((mice.position.altitude.unwrap() - 328.084_f32).abs() < 0.01);
+    assert_eq!(mice.position.comment, b"Hi".to_vec());
+    assert_eq!(mice.position.lat, 0_f32);
+}
+
+#[test]
+fn test_decode_mice_messages() {
+    assert_eq!(MicEMessage::from_bits(0b111, false), MicEMessage::OffDuty);
+    assert_eq!(MicEMessage::from_bits(0b001, false), MicEMessage::Priority);
+    assert_eq!(MicEMessage::from_bits(0b110, true), MicEMessage::Custom(1));
+    assert_eq!(MicEMessage::from_bits(0, true), MicEMessage::Emergency);
+
+    let frame = functions::parse_frame(b"W2GMD-9>APP000:`dINn\"O>/").unwrap();
+    assert_eq!(MicE::from_frame(&frame).unwrap().message, MicEMessage::Unknown);
+}
+
+#[test]
+fn test_decode_mice_ambiguity() {
+    let frame = functions::parse_frame(b"W2GMD-9>S32UZZ:`dINn\"O>/").unwrap();
+    let mice = MicE::from_frame(&frame).unwrap();
+    assert_eq!(mice.position.ambiguity, 2);
+    // Centred on the minute: 33 deg 25.5 min N, 172 deg 45.5 min W
+    assert!((mice.position.lat - (33.0 + 25.5 / 60.0)).abs() < 1e-5);
+    assert!((mice.position.lng + (172.0 + 45.5 / 60.0)).abs() < 1e-4);
+}
+
+#[test]
+fn test_decode_mice_errors() {
+    let frame = functions::parse_frame(b"W2GMD-9>S32U6:`dINn\"O>/").unwrap();
+    assert_eq!(MicE::from_frame(&frame), Err(ParseError::Address { offset: 5 }));
+    let frame = functions::parse_frame(b"W2GMD-9>S32M6T:`dINn\"O>/").unwrap();
+    assert_eq!(MicE::from_frame(&frame), Err(ParseError::Address { offset: 3 }));
+    let frame = functions::parse_frame(b"W2GMD-9>S32U6T:`dINn\"O").unwrap();
+    assert_eq!(MicE::from_frame(&frame), Err(ParseError::Position { offset: 7 }));
+    let frame = functions::parse_frame(b"W2GMD-9>S32U6T:!dINn\"O>/").unwrap();
+    assert_eq!(MicE::from_frame(&frame), Err(ParseError::DataType { offset: 0, identifier: b'!' }));
+}
+
+#[test]
+fn test_encode_mice() {
+    let frame = functions::parse_frame(b"W2GMD-9>S32U6T,WIDE1-1:`dINn\"O>/Hello").unwrap();
+    let mice = MicE::from_frame(&frame).unwrap();
+    let (destination, info) = mice.encode();
+    assert_eq!(destination.callsign, "S32U6T");
+    assert_eq!(info, b"`dINn\"O>/Hello".to_vec());
+}
+
+#[test]
+fn test_encode_mice_round_trip() {
+    let mut position = PositionFrame::new();
+    position.set_source(b"W2GMD-9").unwrap();
+    position.lat = -33.4273_f32;
+    position.lng = 112.129_f32;
+    position.table = b"/".to_vec();
+    position.symbol = b'[';
+    position.course = Some(5);
+    position.speed = Some(3_f32);
+    position.altitude = Some(1000_f32);
+    position.comment = b"On foot".to_vec();
+
+    for message in &[MicEMessage::OffDuty, MicEMessage::Emergency, MicEMessage::Custom(3),
+                     MicEMessage::Unknown] {
+        let (destination, info) = MicE::new(position.clone(), *message).encode();
+
+        let mut frame = Frame::new();
+        frame.source = position.source.clone();
+        frame.destination = destination;
+        frame.set_info(&info).unwrap();
+        assert!(frame.encode_ax25().len() > info.len());
+
+        let decoded = MicE::from_frame(&frame).unwrap();
+        assert_eq!(decoded.message, *message);
+        assert!((decoded.position.lat - position.lat).abs() < 0.0001);
+        assert!((decoded.position.lng - position.lng).abs() < 0.0001);
+        assert_eq!(decoded.position.course, Some(5));
+        assert_eq!(decoded.position.speed, Some(3_f32));
+        assert!((decoded.position.altitude.unwrap() - 1000_f32).abs() < 2_f32);
+        assert_eq!(decoded.position.comment, b"On foot".to_vec());
+    }
+}
+
+#[test]
+fn test_encode_mice_ambiguity() {
+    let mut position = PositionFrame::new();
+    position.lat = 33.4273_f32;
+    position.lng = 112.129_f32;
+    position.ambiguity = 3;
+    position.table = b"/".to_vec();
+    position.symbol = b'>';
+
+    let (destination, info) = MicE::new(position, MicEMessage::OffDuty).encode();
+    assert_eq!(destination.callsign, "SSRZZL");
+    // 112 deg 07.74 min E is sent as 112 deg 00.00 min E
+    assert_eq!(&info[1..4], &[b'(', 88, 28]);
+}
@@ -0,0 +1,82 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::payload::DataType;
+use aprs::nmea::{Nmea, SentenceType, FixQuality, parse_nmea};
+use aprs::structs::PositionFrame;
+use aprs::timestamp::Timestamp;
+use aprs::errors::ParseError;
+
+mod common;
+use common::assert_parse_errors;
+
+#[test]
+fn test_parse_rmc() {
+    let frame = functions::parse_frame(
+        b"N0CALL>GPS:$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::Nmea));
+    let nmea = Nmea::from_frame(&frame).unwrap();
+    assert_eq!(nmea.talker, "GP");
+    assert_eq!(nmea.sentence, SentenceType::Rmc);
+    assert_eq!(nmea.time, Some("123519".to_string()));
+    assert_eq!(nmea.date, Some("230394".to_string()));
+    assert!(nmea.valid);
+    assert_eq!(nmea.lat, Some(48.1173));
+    assert!((nmea.lng.unwrap() - 11.516_667).abs() < 1e-5);
+    assert_eq!(nmea.speed, Some(22.4));
+    assert_eq!(nmea.course, Some(84.4));
+
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    assert_eq!(position.lat, 48.1173);
+    assert_eq!(position.timestamp, Some(Timestamp::Hms { hour: 12, minute: 35, second: 19 }));
+    assert_eq!(position.course, Some(84));
+    assert_eq!(position.speed, Some(22.4));
+    assert!(!position.messaging);
+}
+
+#[test]
+fn test_parse_gga() {
+    let nmea = parse_nmea(b"$GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,\r\n").unwrap();
+    assert_eq!(nmea.sentence, SentenceType::Gga);
+    assert_eq!(nmea.time, Some("123519".to_string()));
+    assert_eq!(nmea.fix_quality, Some(FixQuality::Gps));
+    assert_eq!(nmea.satellites, Some(8));
+    assert_eq!(nmea.altitude, Some(545.4));
+    assert!(nmea.valid);
+    assert!((nmea.to_position().unwrap().altitude.unwrap() - 1789.37).abs() < 0.01);
+
+    let nmea = parse_nmea(b"$GNGGA,,,,,,0,,,,,,,,").unwrap();
+    assert_eq!(nmea.talker, "GN");
+    assert_eq!(nmea.fix_quality, Some(FixQuality::Invalid));
+    assert!(!nmea.valid);
+    assert_eq!(nmea.lat, None);
+    assert_eq!(nmea.to_position(), None);
+}
+
+#[test]
+fn test_parse_gll_vtg() {
+    let nmea = parse_nmea(b"$GPGLL,4916.45,N,12311.12,W,225444,A,*1D").unwrap();
+    assert_eq!(nmea.sentence, SentenceType::Gll);
+    assert!((nmea.lat.unwrap() - 49.274_167).abs() < 1e-5);
+    assert!((nmea.lng.unwrap() + 123.185_33).abs() < 1e-4);
+    assert_eq!(nmea.time, Some("225444".to_string()));
+
+    let nmea = parse_nmea(b"$GPVTG,054.7,T,034.4,M,005.5,N,010.2,K*48").unwrap();
+    assert_eq!(nmea.course, Some(54.7));
+    assert_eq!(nmea.speed, Some(5.5));
+    let nmea = parse_nmea(b"$GPVTG,054.7,034.4,005.5,010.2*54").unwrap();
+    assert_eq!(nmea.speed, Some(5.5));
+    assert!(PositionFrame::from_frame(&functions::parse_frame(
+        b"N0CALL>GPS:$GPVTG,054.7,034.4,005.5,010.2*54").unwrap()).is_err());
+}
+
+#[test]
+fn test_parse_nmea_errors() {
+    assert_parse_errors(parse_nmea, &[
+        (b"$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6B", ParseError::InfoField { offset: 66 }),
+        (b"$GPRMC,123519,A,4807.038,X,01131.000,E,022.4,084.4,230394,003.1,W*7C", ParseError::InfoField { offset: 25 }),
+        (b"$GPRMC,12351,A", ParseError::InfoField { offset: 7 }),
+        (b"$GPXYZ,1", ParseError::InfoField { offset: 3 }),
+        (b"$GPRMC*zz", ParseError::InfoField { offset: 7 }),
+        (b"!GPRMC", ParseError::DataType { offset: 0, identifier: b'!' }),
+    ]);
+}
@@ -0,0 +1,105 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::object::{Object, Item, parse_object, parse_item};
+use aprs::timestamp::Timestamp;
+use aprs::errors::{ParseError, EncodeError};
+
+mod common;
+use common::{assert_parse_errors, assert_round_trip};
+
+#[test]
+fn test_parse_object() {
+    let frame = functions::parse_frame(b"W2GMD>APRS:;LEADER   *092345z4903.50N/07201.75W>088/036").unwrap();
+    let object = Object::from_frame(&frame).unwrap();
+    assert_eq!(object.name, "LEADER");
+    assert!(object.live);
+    assert_eq!(object.timestamp, Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 });
+    assert_eq!(object.position.lat, 49.058_334_f32);
+    assert_eq!(object.position.symbol, b'>');
+    assert_eq!(object.position.course, Some(88));
+    assert_eq!(object.position.speed, Some(36.0));
+    assert!(object.position.comment.is_empty());
+}
+
+#[test]
+fn test_parse_object_compressed_killed() {
+    let object = parse_object(b";Aid 3    _111111z/5L!!<*e7>7P[").unwrap();
+    assert_eq!(object.name, "Aid 3");
+    assert!(!object.live);
+    assert!(object.position.compressed);
+    assert_eq!(object.position.course, Some(88));
+}
+
+#[test]
+fn test_parse_object_errors() {
+    assert_parse_errors(parse_object, &[
+        (b";LEADER   x092345z4903.50N/07201.75W>", ParseError::InfoField { offset: 10 }),
+        (b";LEADER   *0923x5z4903.50N/07201.75W>", ParseError::Position { offset: 15 }),
+        (b";LEADER   *092345z4903.50N/07201.75W", ParseError::Position { offset: 36 }),
+    ]);
+}
+
+#[test]
+fn test_parse_item() {
+    let item = parse_item(b")AID #2!4903.50N/07201.75WA").unwrap();
+    assert_eq!(item.name, "AID #2");
+    assert!(item.live);
+    assert_eq!(item.position.symbol, b'A');
+
+    let item = parse_item(b")G_4903.50N/07201.75WA");
+    assert_eq!(item, Err(ParseError::InfoField { offset: 2 }));
+
+    let item = parse_item(b")ABC_/5L!!<*e7>7P[").unwrap();
+    assert!(!item.live);
+    assert!(item.position.compressed);
+}
+
+#[test]
+fn test_object_round_trip() {
+    let raw: &[u8] = b";LEADER   *092345z4903.50N/07201.75W>088/036";
+    assert_round_trip(parse_object, Object::create_info_field, &[raw]);
+    let mut object = parse_object(raw).unwrap();
+
+    object.live = false;
+    assert_eq!(object.create_info_field().unwrap()[10], b'_');
+
+    object.name = "TOO LONG NAME".to_string();
+    assert_eq!(object.create_info_field(),
+               Err(EncodeError::TooLong { field: "object name", max: 9 }));
+}
+
+#[test]
+fn test_item_round_trip() {
+    let raw: &[u8] = b")AID #2!4903.50N/07201.75WA";
+    assert_round_trip(parse_item, Item::create_info_field, &[raw]);
+    let mut item = parse_item(raw).unwrap();
+
+    item.name = "AI".to_string();
+    assert_eq!(item.create_info_field(), Err(EncodeError::TooShort { field: "item name", min: 3 }));
+    item.name = "AID_2".to_string();
+    assert_eq!(item.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "item name", offset: 3 }));
+}
+
+#[test]
+fn test_symbol_errors() {
+    let mut object = Object::new();
+    object.name = "TEST".to_string();
+    assert_eq!(object.create_info_field(), Err(EncodeError::Missing { field: "symbol table" }));
+    object.position.table = b"a".to_vec();
+    assert_eq!(object.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "symbol table", offset: 0 }));
+    object.position.table = b"//".to_vec();
+    assert_eq!(object.create_info_field(),
+               Err(EncodeError::InvalidCharacter { field: "symbol table", offset: 1 }));
+    object.position.table = b"/".to_vec();
+    assert_eq!(object.create_info_field(), Err(EncodeError::Missing { field: "symbol" }));
+    object.position.symbol = b' ';
+    assert_eq!(object.create_info_field(), Err(EncodeError::InvalidCharacter { field: "symbol", offset: 0 }));
+    object.position.symbol = b'>';
+    assert!(object.create_info_field().is_ok());
+
+    let mut item = parse_item(b")AID #2!4903.50N/07201.75WA").unwrap();
+    item.position.table = Vec::new();
+    assert_eq!(item.create_info_field(), Err(EncodeError::Missing { field: "symbol table" }));
+}
@@ -0,0 +1,180 @@
+extern crate aprs;
+use std::time::Duration;
+
+use aprs::clock::ManualClock;
+use aprs::functions;
+use aprs::object::{Object, Item};
+use aprs::object_manager::{ObjectManager, ObjectEvent};
+use aprs::structs::Frame;
+use aprs::timestamp::Timestamp;
+
+fn manager(clock: &ManualClock) -> ObjectManager<&ManualClock> {
+    ObjectManager::new(functions::parse_callsign(b"W2GMD-6").unwrap(), clock)
+}
+
+fn object(name: &str) -> Object {
+    let mut object = Object::new();
+    object.name = name.to_string();
+    object.timestamp = Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 };
+    object.position.lat = 49.058333;
+    object.position.lng = -72.02917;
+    object.position.table = b"/".to_vec();
+    object.position.symbol = b'>';
+    object
+}
+
+fn item(name: &str) -> Item {
+    let mut item = Item::new();
+    item.name = name.to_string();
+    item.position.table = b"/".to_vec();
+    item.position.symbol = b'>';
+    item
+}
+
+#[test]
+fn test_decaying_schedule() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.set_max_interval(Duration::from_secs(30));
+    manager.add_object(object("AID1")).unwrap();
+
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].source.to_string(), "W2GMD-6");
+    assert_eq!(frames[0].info.data[..19].to_vec(), b";AID1     *092345z4".to_vec());
+
+    // Repeats after 8, 16 and then every 30 seconds
+    for &(wait, sent) in &[(7, 0), (1, 1), (15, 0), (1, 1), (29, 0), (1, 1), (30, 1)] {
+        clock.advance(Duration::from_secs(wait));
+        assert_eq!(manager.poll().len(), sent);
+    }
+}
+
+#[test]
+fn test_update_restarts_schedule() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.add_object(object("AID1")).unwrap();
+    manager.poll();
+    clock.advance(Duration::from_secs(2));
+
+    let mut moved = object("AID1");
+    moved.position.comment = b"moved".to_vec();
+    manager.add_object(moved).unwrap();
+    assert_eq!(manager.reports().len(), 1);
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert!(frames[0].info.data.ends_with(b"moved"));
+    assert!(manager.add_object(object("TOOLONGNAME")).is_err());
+}
+
+#[test]
+fn test_remove_sends_kills() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.set_kill_count(2);
+    manager.add_item(item("CHK2")).unwrap();
+    manager.poll();
+
+    assert!(manager.remove("CHK2"));
+    assert!(!manager.remove("CHK2"));
+    assert!(manager.reports().is_empty());
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames[0].info.data[..6].to_vec(), b")CHK2_".to_vec());
+    clock.advance(Duration::from_secs(8));
+    assert_eq!(manager.poll().len(), 1);
+    clock.advance(Duration::from_secs(600));
+    assert!(manager.poll().is_empty());
+}
+
+#[test]
+fn test_add_matches_kind_and_cancels_kills() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.add_object(object("AID1")).unwrap();
+    manager.add_item(item("AID1")).unwrap();
+    assert_eq!(manager.reports().len(), 2);
+    manager.poll();
+
+    assert!(manager.remove("AID1"));
+    assert_eq!(manager.poll()[0].info.data[..11].to_vec(), b";AID1     _".to_vec());
+    manager.add_object(object("AID1")).unwrap();
+    assert_eq!(manager.reports().len(), 2);
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].info.data[..11].to_vec(), b";AID1     *".to_vec());
+    clock.advance(Duration::from_secs(8));
+    assert!(manager.poll().iter().all(|frame| frame.info.data[10] != b'_'));
+}
+
+#[test]
+fn test_long_intervals() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.set_initial_interval(Duration::MAX);
+    manager.set_max_interval(Duration::MAX);
+    manager.set_lifetime(Some(Duration::MAX));
+    manager.add_object(object("AID1")).unwrap();
+    assert_eq!(manager.poll().len(), 1);
+    clock.advance(Duration::from_secs(60));
+    assert!(manager.poll().is_empty());
+}
+
+#[test]
+fn test_lifetime_ages_out() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.set_lifetime(Some(Duration::from_secs(60)));
+    manager.add_object(object("AID1")).unwrap();
+    manager.poll();
+
+    clock.advance(Duration::from_secs(60));
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].info.data[10], b'_');
+    assert!(manager.reports().is_empty());
+}
+
+#[test]
+fn test_takeover() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.add_object(object("AID1")).unwrap();
+    manager.add_object(object("AID2")).unwrap();
+
+    // Our own transmissions heard back through a digipeater are not a takeover
+    manager.receive(&functions::parse_frame(
+        b"W2GMD-6>APRS,WIDE1-1:;AID1     *092345z4903.50N/07201.75W>").unwrap());
+    assert!(manager.events().is_empty());
+
+    manager.receive(&functions::parse_frame(
+        b"KF4MKT>APRS:;AID1     *092350z4903.60N/07201.75W>").unwrap());
+    assert_eq!(manager.events(), vec![ObjectEvent::TakenOver {
+        name: "AID1".to_string(),
+        station: functions::parse_callsign(b"KF4MKT").unwrap(),
+        live: true,
+    }]);
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].info.data[..5].to_vec(), b";AID2".to_vec());
+}
+
+#[test]
+fn test_takeover_matches_live_reports_of_the_same_kind() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut manager = manager(&clock);
+    manager.add_object(object("AID1")).unwrap();
+    manager.add_item(item("CHK2")).unwrap();
+    manager.poll();
+    assert!(manager.remove("CHK2"));
+
+    // An item named like our object, and an object named like our item being killed
+    manager.receive(&functions::parse_frame(b"KF4MKT>APRS:)AID1!4903.60N/07201.75W>").unwrap());
+    manager.receive(&functions::parse_frame(
+        b"KF4MKT>APRS:;CHK2     *092350z4903.60N/07201.75W>").unwrap());
+    assert!(manager.events().is_empty());
+    assert_eq!(manager.reports().len(), 1);
+    let frames: Vec<Frame> = manager.poll();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].info.data[..6].to_vec(), b")CHK2_".to_vec());
+}
@@ -0,0 +1,35 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::errors::ParseError;
+
+#[test]
+fn test_parse_callsign_text() {
+    let call = functions::parse_callsign_text(b"KF4LZA-15").unwrap();
+    assert_eq!(call.callsign, "KF4LZA");
+    assert_eq!(call.ssid, 15);
+    assert!(!call.digi);
+
+    let call = functions::parse_callsign_text(b"WIDE2-1*").unwrap();
+    assert_eq!(call.callsign, "WIDE2");
+    assert_eq!(call.ssid, 1);
+    assert!(call.digi);
+}
+
+#[test]
+fn test_parse_callsign_text_errors() {
+    assert_eq!(functions::parse_callsign_text(b"W2GMD-A"), Err(ParseError::Ssid { offset: 6 }));
+    assert_eq!(functions::parse_callsign_text(b"*W2GMD-A"), Err(ParseError::Ssid { offset: 7 }));
+    assert_eq!(functions::parse_callsign_text(b"W2\xc3"), Err(ParseError::Address { offset: 2 }));
+    assert_eq!(functions::parse_callsign_text(b""), Err(ParseError::Address { offset: 0 }));
+}
+
+#[test]
+fn test_parse_callsign_ax25() {
+    let raw: &[u8] = &[0xae, 0x64, 0x8e, 0x9a, 0x88, 0x40, 0x6c];
+    let call = functions::parse_callsign_ax25(raw, false).unwrap();
+    assert_eq!(call.callsign, "W2GMD");
+    assert_eq!(call.ssid, 6);
+
+    assert_eq!(functions::parse_callsign_ax25(&raw[..5], false),
+               Err(ParseError::Address { offset: 5 }));
+}
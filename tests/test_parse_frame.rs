@@ -0,0 +1,65 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::payload::DataType;
+use aprs::errors::ParseError;
+
+#[test]
+fn test_parse_frame_text() {
+    let frame = functions::parse_frame(b"W2GMD-6>APRS,WIDE1-1*,WIDE2-1:>Inner Sunset").unwrap();
+    assert_eq!(frame.source.callsign, "W2GMD");
+    assert_eq!(frame.source.ssid, 6);
+    assert_eq!(frame.destination.callsign, "APRS");
+    assert_eq!(frame.path.len(), 2);
+    assert_eq!(frame.path[0].callsign, "WIDE1");
+    assert!(frame.path[0].digi);
+    assert_eq!(frame.info.data_type, Some(DataType::Status));
+}
+
+#[test]
+fn test_parse_frame_text_missing_delimiters() {
+    assert_eq!(functions::parse_frame(b"W2GMD-6APRS:>status"),
+               Err(ParseError::Address { offset: 19 }));
+    assert_eq!(functions::parse_frame(b"W2GMD-6>APRS>status"),
+               Err(ParseError::Address { offset: 19 }));
+}
+
+#[test]
+fn test_parse_frame_text_bad_path() {
+    assert_eq!(functions::parse_frame(b"W2GMD-6>APRS,WIDE1-X:>status"),
+               Err(ParseError::Ssid { offset: 19 }));
+    assert_eq!(functions::parse_frame(b"W2GMD-6>APRS,,WIDE1-1:>status"),
+               Err(ParseError::Path { offset: 13 }));
+    assert_eq!(functions::parse_frame(b"W2GMD-6>AP\xffRS:>status"),
+               Err(ParseError::Address { offset: 10 }));
+}
+
+#[test]
+fn test_parse_frame_text_bad_info() {
+    assert_eq!(functions::parse_frame(b"W2GMD-6>APRS:"),
+               Err(ParseError::InfoField { offset: 13 }));
+    // Unassigned identifiers are kept rather than rejected
+    let frame = functions::parse_frame(b"W2GMD-6>APRS:\x01status").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::Unknown(0x01)));
+}
+
+#[test]
+fn test_parse_frame_ax25_short_address() {
+    let raw: &[u8] = &[0x82, 0xa0, 0xa4, 0xa6, 0x40, 0x40, 0x60, 0x03, 0xf0, b'>'];
+    assert_eq!(functions::parse_frame(raw), Err(ParseError::Address { offset: 7 }));
+}
+
+#[test]
+fn test_encode_frame_ax25() {
+    let frame = functions::parse_frame(b"N0CALL>APRS,WIDE1-1:>Test").unwrap();
+    let encoded: Vec<u8> = frame.encode_ax25();
+    assert_eq!(encoded, vec![
+        0x7e,
+        0x82, 0xa0, 0xa4, 0xa6, 0x40, 0x40, 0x60,  // APRS
+        0x9c, 0x60, 0x86, 0x82, 0x98, 0x98, 0x60,  // N0CALL
+        0xae, 0x92, 0x88, 0x8a, 0x62, 0x40, 0x63,  // WIDE1-1, end of address
+        0x03, 0xf0,
+        b'>', b'T', b'e', b's', b't',
+        0x0f, 0xff,
+        0x7e,
+    ]);
+}
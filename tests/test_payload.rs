@@ -0,0 +1,85 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::payload::{DataType, Payload};
+use aprs::errors::ParseError;
+
+#[test]
+fn test_data_types() {
+    let cases: [(&[u8], DataType); 26] = [
+        (b"!", DataType::PositionNoTimestamp),
+        (b"=", DataType::PositionNoTimestampMessaging),
+        (b"/", DataType::PositionTimestamp),
+        (b"@", DataType::PositionTimestampMessaging),
+        (b"`", DataType::MicE),
+        (b"\x1c", DataType::MicE),
+        (b"'", DataType::OldMicE),
+        (b"\x1d", DataType::OldMicE),
+        (b":", DataType::Message),
+        (b"T", DataType::Telemetry),
+        (b";", DataType::Object),
+        (b")", DataType::Item),
+        (b">", DataType::Status),
+        (b"_", DataType::Weather),
+        (b"*", DataType::PeetBros),
+        (b"$GPRMC", DataType::Nmea),
+        (b"$ULTW", DataType::Ultimeter),
+        (b"!!", DataType::Ultimeter),
+        (b"}", DataType::ThirdParty),
+        (b"<", DataType::Capabilities),
+        (b"?", DataType::Query),
+        (b"%", DataType::Agrelo),
+        (b",", DataType::Test),
+        (b"[", DataType::Maidenhead),
+        (b"{", DataType::UserDefined),
+        (b"&", DataType::Unknown(b'&')),
+    ];
+    for &(data, data_type) in cases.iter() {
+        assert_eq!(DataType::from_data(data), Some(data_type));
+    }
+    assert_eq!(DataType::from_data(b""), None);
+}
+
+#[test]
+fn test_decode_payloads() {
+    let payload = |raw: &[u8]| Payload::from_frame(&functions::parse_frame(raw).unwrap());
+
+    match payload(b"N0CALL>APRS:=4903.50N/07201.75W-Home").unwrap() {
+        Payload::Position(position) => assert_eq!(position.comment, b"Home".to_vec()),
+        other => panic!("{:?}", other),
+    }
+    match payload(b"W2GMD-9>S32U6T:`dINn\"O>/").unwrap() {
+        Payload::MicE(mice) => assert_eq!(mice.position.symbol, b'>'),
+        other => panic!("{:?}", other),
+    }
+    match payload(b"N0CALL>APRS::W1AW     :Hello{01").unwrap() {
+        Payload::Message(message) => assert_eq!(message.text, "Hello"),
+        other => panic!("{:?}", other),
+    }
+    match payload(b"N0CALL>APRS:;LEADER   *092345z4903.50N/07201.75W>").unwrap() {
+        Payload::Object(object) => assert_eq!(object.name, "LEADER"),
+        other => panic!("{:?}", other),
+    }
+    match payload(b"N0CALL>APRS:_10090556c220s004g005t077").unwrap() {
+        Payload::Weather(weather) => assert_eq!(weather.temperature, Some(77)),
+        other => panic!("{:?}", other),
+    }
+    match payload(b"N0CALL>APRS:}W1AW>APRS,TCPIP,N0CALL*:>Hi").unwrap() {
+        Payload::ThirdParty(third_party) => assert_eq!(third_party.inner.source.callsign, "W1AW"),
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(payload(b"N0CALL>APRS:[IO91SX]35 miles NNW of London").unwrap(),
+               Payload::Maidenhead { grid: "IO91SX".to_string(), comment: b"35 miles NNW of London".to_vec() });
+    assert_eq!(payload(b"N0CALL>APRS:{Q1data").unwrap(),
+               Payload::UserDefined { user_id: b'Q', packet_type: b'1', data: b"data".to_vec() });
+    assert_eq!(payload(b"N0CALL>APRS:,test").unwrap(), Payload::Test(b"test".to_vec()));
+    assert_eq!(payload(b"N0CALL>APRS:&raw").unwrap(), Payload::Unknown(b"&raw".to_vec()));
+}
+
+#[test]
+fn test_decode_payload_errors() {
+    let payload = |raw: &[u8]| Payload::from_frame(&functions::parse_frame(raw).unwrap());
+    assert_eq!(payload(b"N0CALL>APRS:!4903.50N"), Err(ParseError::Position { offset: 9 }));
+    assert_eq!(payload(b"N0CALL>APRS:[IO91S]"), Err(ParseError::InfoField { offset: 6 }));
+    assert_eq!(payload(b"N0CALL>APRS:[IO9!]"), Err(ParseError::InfoField { offset: 4 }));
+    assert_eq!(payload(b"N0CALL>APRS:{Q"), Err(ParseError::InfoField { offset: 2 }));
+}
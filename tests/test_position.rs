@@ -0,0 +1,124 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::structs::{PositionFrame, CompressionType, CompressionOrigin, NmeaSource};
+use aprs::timestamp::Timestamp;
+use aprs::errors::ParseError;
+
+#[test]
+fn test_decode_position_no_timestamp() {
+    let frame = functions::parse_frame(b"W2GMD-6>APRS:!3745.75N/12228.05W#Inner Sunset").unwrap();
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    assert_eq!(position.source.callsign, "W2GMD");
+    assert_eq!(position.lat, 37.762_5_f32);
+    assert_eq!(position.lng, -122.467_5_f32);
+    assert_eq!(position.table, b"/".to_vec());
+    assert_eq!(position.symbol, b'#');
+    assert_eq!(position.comment, b"Inner Sunset".to_vec());
+    assert_eq!(position.ambiguity, 0);
+    assert_eq!(position.timestamp, None);
+    assert!(!position.messaging);
+    assert_eq!(position.overlay(), None);
+}
+
+#[test]
+fn test_decode_position_timestamp_overlay() {
+    let frame = functions::parse_frame(b"W2GMD>APRS:@092345z4903.50NS07201.75W#").unwrap();
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    assert_eq!(position.timestamp, Some(Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 }));
+    assert!(position.messaging);
+    assert_eq!(position.overlay(), Some(b'S'));
+    assert!(position.comment.is_empty());
+}
+
+#[test]
+fn test_position_round_trip() {
+    let raw: &[u8] = b"/092345z4903.50N/07201.75W>Test";
+    let position = functions::parse_position(raw).unwrap();
+    assert_eq!(position.create_info_field(), raw.to_vec());
+
+    let raw: &[u8] = b"=4903.5 N/07201.7 W-";
+    let position = functions::parse_position(raw).unwrap();
+    assert_eq!(position.ambiguity, 1);
+    assert_eq!(position.create_info_field(), raw.to_vec());
+}
+
+#[test]
+fn test_decode_position_ambiguity() {
+    let position = functions::parse_position(b"=4903.5 N/07201.7 W-").unwrap();
+    assert!((position.lat - (49.0 + 3.55 / 60.0)).abs() < 1e-5);
+    assert!((position.lng + (72.0 + 1.75 / 60.0)).abs() < 1e-5);
+    assert!((position.ambiguity_radius().unwrap() - 110.71).abs() < 0.1);
+
+    // The latitude's ambiguity is applied to the longitude
+    let position = functions::parse_position(b"=49  .  S/07201.75E-").unwrap();
+    assert_eq!(position.ambiguity, 4);
+    assert_eq!(position.lat, -49.5);
+    assert_eq!(position.lng, 72.5);
+    assert_eq!(position.create_info_field(), b"=49  .  S/072  .  E-".to_vec());
+
+    let position = functions::parse_position(b"=4903.50N/07201.75W-").unwrap();
+    assert_eq!(position.ambiguity_radius(), None);
+
+    assert_eq!(functions::parse_position(b"=4   .  N/07201.75W-"),
+               Err(ParseError::Position { offset: 1 }));
+}
+
+#[test]
+fn test_decode_position_errors() {
+    assert_eq!(functions::parse_position(b"!4903.50N/07201.75W"),
+               Err(ParseError::Position { offset: 19 }));
+    assert_eq!(functions::parse_position(b"!4903.50N|07201.75W-"),
+               Err(ParseError::Position { offset: 9 }));
+    assert_eq!(functions::parse_position(b"!4903.50N/07201.75X-"),
+               Err(ParseError::Position { offset: 10 }));
+    assert_eq!(functions::parse_position(b"@0923a5z4903.50N/07201.75W-"),
+               Err(ParseError::Position { offset: 5 }));
+    assert_eq!(functions::parse_position(b">status"),
+               Err(ParseError::DataType { offset: 0, identifier: b'>' }));
+}
+
+#[test]
+fn test_decode_compressed_course_speed() {
+    let position = functions::parse_position(b"=/5L!!<*e7>7P[Comment").unwrap();
+    assert!(position.compressed);
+    assert_eq!(position.lat, 49.5_f32);
+    assert!((position.lng - -72.75_f32).abs() < 0.0001);
+    assert_eq!(position.table, b"/".to_vec());
+    assert_eq!(position.symbol, b'>');
+    assert_eq!(position.course, Some(88));
+    assert!((position.speed.unwrap() - 36.2_f32).abs() < 0.1);
+    assert_eq!(position.comment, b"Comment".to_vec());
+
+    let comp_type: CompressionType = position.compression_type.unwrap();
+    assert!(comp_type.current_fix);
+    assert_eq!(comp_type.nmea_source, NmeaSource::Rmc);
+    assert_eq!(comp_type.origin, CompressionOrigin::Software);
+
+    let encoded: Vec<u8> = position.create_info_field();
+    assert_eq!(&encoded[..6], b"=/5L!!");
+    assert_eq!(&encoded[10..], b">7P[Comment");
+}
+
+#[test]
+fn test_decode_compressed_range_altitude() {
+    let position = functions::parse_position(b"!\\5L!!<*e7>{?!").unwrap();
+    assert!((position.range.unwrap() - 20.12_f32).abs() < 0.01);
+    assert_eq!(position.course, None);
+
+    let position = functions::parse_position(b"!/5L!!<*e7>S]1").unwrap();
+    assert!((position.altitude.unwrap() - 10004_f32).abs() < 1_f32);
+    assert_eq!(&position.create_info_field()[10..], b">S]1");
+
+    let position = functions::parse_position(b"!a5L!!<*e7#   ").unwrap();
+    assert_eq!(position.overlay(), Some(b'0'));
+    assert_eq!(position.compression_type, None);
+    assert_eq!(&position.create_info_field()[..6], b"!a5L!!");
+}
+
+#[test]
+fn test_decode_compressed_errors() {
+    assert_eq!(functions::parse_position(b"!/5L!!<*e7>7P"),
+               Err(ParseError::Position { offset: 13 }));
+    assert_eq!(functions::parse_position(b"!/5L !<*e7>7P["),
+               Err(ParseError::Position { offset: 2 }));
+}
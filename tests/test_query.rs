@@ -0,0 +1,174 @@
+extern crate aprs;
+use std::time::Duration;
+
+use aprs::clock::ManualClock;
+use aprs::errors::ParseError;
+use aprs::functions;
+use aprs::message::Message;
+use aprs::payload::{DataType, Payload};
+use aprs::query::{Footprint, Query, QueryType, parse_query};
+use aprs::query_responder::QueryResponder;
+use aprs::random::Random;
+use aprs::structs::{Frame, PositionFrame};
+
+mod common;
+use common::assert_parse_errors;
+
+struct Fixed(u64);
+
+impl Random for Fixed {
+    fn next_u64(&mut self) -> u64 {
+        self.0
+    }
+}
+
+fn position() -> PositionFrame {
+    let mut position: PositionFrame = PositionFrame::new();
+    position.lat = 34.05;
+    position.lng = -117.2;
+    position.table = b"/".to_vec();
+    position.symbol = b'-';
+    position
+}
+
+fn responder(clock: &ManualClock, delay_ms: u64) -> QueryResponder<&ManualClock, Fixed> {
+    let mut responder = QueryResponder::new(functions::parse_callsign(b"W2GMD-6").unwrap(), clock,
+                                            Fixed(delay_ms));
+    responder.set_position(position());
+    responder.set_status("Net Control Center");
+    responder
+}
+
+#[test]
+fn test_parse_general_queries() {
+    assert_eq!(parse_query(b"?APRS?").unwrap(), Query { query_type: QueryType::Aprs, footprint: None });
+    assert_eq!(parse_query(b"?IGATE?").unwrap().query_type, QueryType::Igate);
+    assert_eq!(parse_query(b"?WX?\r").unwrap().query_type, QueryType::Weather);
+    assert_eq!(parse_query(b"?PING?").unwrap().query_type, QueryType::Ping);
+    assert_eq!(parse_query(b"?FOO?").unwrap().query_type, QueryType::Other("FOO".to_string()));
+
+    let query: Query = parse_query(b"?APRS? 34.02,-117.15,0200").unwrap();
+    assert_eq!(query.footprint, Some(Footprint { lat: 34.02, lng: -117.15, radius: 200 }));
+    let footprint: Footprint = query.footprint.unwrap();
+    assert!(footprint.contains(34.05, -117.2));
+    assert!(!footprint.contains(37.77, -122.42));
+}
+
+#[test]
+fn test_parse_query_errors() {
+    assert_parse_errors(parse_query, &[
+        (b"?APRS", ParseError::InfoField { offset: 5 }),
+        (b"??", ParseError::InfoField { offset: 1 }),
+        (b">APRS?", ParseError::DataType { offset: 0, identifier: b'>' }),
+        (b"?APRS? 95.0,-117.15,0200", ParseError::InfoField { offset: 7 }),
+        (b"?APRS? 34.02,-117.15,x", ParseError::InfoField { offset: 21 }),
+        (b"?APRS? 34.02,-117.15,0200,1", ParseError::InfoField { offset: 25 }),
+    ]);
+}
+
+#[test]
+fn test_directed_queries() {
+    let query = |text: &[u8]| -> Option<QueryType> {
+        let frame: Frame = functions::parse_frame(text).unwrap();
+        let message: Message = Message::from_info_field(&frame.info).unwrap();
+        Query::from_message(&message).map(|query| query.query_type)
+    };
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :?APRSP"), Some(QueryType::Position));
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :?aprss{3"), Some(QueryType::Status));
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :?APRSD"), Some(QueryType::Heard));
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :?APRST"), Some(QueryType::Ping));
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :?PING?"), Some(QueryType::Ping));
+    assert_eq!(query(b"N0CALL>APRS::W2GMD-6  :hello"), None);
+}
+
+#[test]
+fn test_query_payload() {
+    match Payload::from_frame(&functions::parse_frame(b"N0CALL>APRS:?IGATE?").unwrap()).unwrap() {
+        Payload::Query(query) => assert_eq!(query.query_type, QueryType::Igate),
+        payload => panic!("unexpected payload {:?}", payload),
+    }
+}
+
+#[test]
+fn test_general_query_delayed() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut responder = responder(&clock, 30_000);
+    assert!(responder.receive(&functions::parse_frame(b"N0CALL>APRS:?APRS?").unwrap()).is_empty());
+    // Repeated queries don't queue the replies twice
+    assert!(responder.receive(&functions::parse_frame(b"N1CALL>APRS:?APRS?").unwrap()).is_empty());
+    assert_eq!(responder.pending(), 2);
+
+    clock.advance(Duration::from_secs(29));
+    assert!(responder.poll().is_empty());
+    clock.advance(Duration::from_secs(1));
+    let replies: Vec<Frame> = responder.poll();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0].source.to_string(), "W2GMD-6");
+    assert_eq!(replies[0].info.data, position().create_info_field());
+    assert_eq!(replies[1].info.data_type, Some(DataType::Status));
+    assert_eq!(replies[1].info.data, b">Net Control Center".to_vec());
+    assert_eq!(responder.pending(), 0);
+}
+
+#[test]
+fn test_general_query_longest_delay() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut responder = responder(&clock, u64::MAX);
+    responder.set_max_delay(Duration::new(u64::MAX, 999_999_999));
+    assert!(responder.receive(&functions::parse_frame(b"N0CALL>APRS:?APRS?").unwrap()).is_empty());
+    clock.advance(Duration::from_secs(u64::MAX / 1000));
+    assert!(responder.poll().is_empty());
+    assert_eq!(responder.pending(), 2);
+}
+
+#[test]
+fn test_general_query_filters() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut responder = responder(&clock, 0);
+    responder.receive(&functions::parse_frame(b"N0CALL>APRS:?APRS? 37.77,-122.42,0050").unwrap());
+    responder.receive(&functions::parse_frame(b"N0CALL>APRS:?WX?").unwrap());
+    responder.receive(&functions::parse_frame(b"N0CALL>APRS:?IGATE?").unwrap());
+    responder.receive(&functions::parse_frame(b"W2GMD-6>APRS:?APRS?").unwrap());
+    assert!(responder.poll().is_empty());
+
+    responder.set_gated_messages(Some(12));
+    responder.set_heard(vec![functions::parse_callsign(b"N0CALL").unwrap()]);
+    responder.receive(&functions::parse_frame(b"N0CALL>APRS:?IGATE?").unwrap());
+    responder.receive(&functions::parse_frame(b"N0CALL>APRS:?APRS? 34.02,-117.15,0200").unwrap());
+    let replies: Vec<Frame> = responder.poll();
+    assert_eq!(replies.len(), 3);
+    assert_eq!(replies[0].info.data, b"<IGATE,MSG_CNT=12,LOC_CNT=1".to_vec());
+}
+
+#[test]
+fn test_directed_query_replies() {
+    let clock = ManualClock::new(Duration::from_secs(0));
+    let mut responder = responder(&clock, 60_000);
+    responder.set_heard(vec![
+        functions::parse_callsign(b"N0CALL").unwrap(),
+        functions::parse_callsign(b"KF4MKT-9").unwrap(),
+    ]);
+
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS::W2GMD-6  :?APRSP{1").unwrap();
+    let replies: Vec<Frame> = responder.receive(&frame);
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].info.data, position().create_info_field());
+
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS::w2gmd-6  :?APRSS").unwrap();
+    let replies: Vec<Frame> = responder.receive(&frame);
+    assert_eq!(replies[0].info.data, b">Net Control Center".to_vec());
+
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS::W2GMD-6  :?APRSD").unwrap();
+    let replies: Vec<Frame> = responder.receive(&frame);
+    assert_eq!(replies[0].info.data, b":N0CALL   :Directs= N0CALL KF4MKT-9".to_vec());
+
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS,WIDE1-1,WIDE2-1::W2GMD-6  :?PING?").unwrap();
+    let replies: Vec<Frame> = responder.receive(&frame);
+    assert_eq!(replies[0].info.data, b":N0CALL   :N0CALL>APRS,WIDE1-1,WIDE2-1".to_vec());
+
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS::N1CALL   :?APRSP").unwrap();
+    assert!(responder.receive(&frame).is_empty());
+    let frame: Frame = functions::parse_frame(b"N0CALL>APRS::W2GMD-6  :?FOO?").unwrap();
+    assert!(responder.receive(&frame).is_empty());
+    assert_eq!(responder.pending(), 0);
+}
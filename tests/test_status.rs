@@ -0,0 +1,95 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::status::{Status, Locator, parse_status};
+use aprs::timestamp::Timestamp;
+use aprs::errors::{ParseError, EncodeError};
+
+mod common;
+use common::{assert_parse_errors, assert_round_trip};
+
+#[test]
+fn test_parse_status_text() {
+    let frame = functions::parse_frame(b"N0CALL>APRS:>Net Control Center").unwrap();
+    let status = Status::from_frame(&frame).unwrap();
+    assert_eq!(status.text, "Net Control Center");
+    assert_eq!(status.timestamp, None);
+    assert_eq!(status.locator, None);
+    assert_eq!(status.beam_heading, None);
+}
+
+#[test]
+fn test_parse_status_timestamp() {
+    let status = parse_status(b">092345zNet Control Center").unwrap();
+    assert_eq!(status.timestamp, Some(Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 }));
+    assert_eq!(status.text, "Net Control Center");
+
+    // Only zulu timestamps are allowed in status reports
+    let status = parse_status(b">092345/Net").unwrap();
+    assert_eq!(status.timestamp, None);
+    assert_eq!(status.text, "092345/Net");
+}
+
+#[test]
+fn test_parse_status_locator() {
+    let status = parse_status(b">IO91SX/G My house").unwrap();
+    assert_eq!(status.locator, Some(Locator { grid: "IO91SX".to_string(), table: b'/', symbol: b'G' }));
+    assert_eq!(status.text, "My house");
+
+    let status = parse_status(b">IO91\\K").unwrap();
+    assert_eq!(status.locator.unwrap().grid, "IO91");
+    assert_eq!(status.text, "");
+
+    assert_eq!(parse_status(b">IO91SX/Gx").unwrap().locator, None);
+}
+
+#[test]
+fn test_parse_status_beam() {
+    let status = parse_status(b">IO91SX/G^B7").unwrap();
+    assert_eq!(status.beam_heading, Some(110));
+    assert_eq!(status.erp, Some(490));
+    assert_eq!(status.text, "");
+
+    let status = parse_status(b">Beam^x7").unwrap();
+    assert_eq!(status.beam_heading, None);
+    assert_eq!(status.text, "Beam^x7");
+}
+
+#[test]
+fn test_parse_status_errors() {
+    assert_parse_errors(parse_status, &[
+        (b"", ParseError::InfoField { offset: 0 }),
+        (b"!x", ParseError::DataType { offset: 0, identifier: b'!' }),
+        (b">ok\xff", ParseError::InfoField { offset: 3 }),
+    ]);
+}
+
+#[test]
+fn test_status_round_trip() {
+    assert_round_trip(parse_status, Status::create_info_field, &[
+        b">Net Control Center", b">092345zNet", b">IO91SX/G My house^B7", b">JO22/-",
+    ]);
+}
+
+#[test]
+fn test_create_status_errors() {
+    let mut status = Status::new();
+    status.text = "a|b".to_string();
+    assert_eq!(status.create_info_field(), Err(EncodeError::InvalidCharacter { field: "text", offset: 1 }));
+
+    status.text = "x".repeat(56);
+    status.timestamp = Some(Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 });
+    assert_eq!(status.create_info_field(), Err(EncodeError::TooLong { field: "text", max: 55 }));
+
+    status.text = String::new();
+    status.locator = Some(Locator { grid: "IO91".to_string(), table: b'/', symbol: b'G' });
+    assert_eq!(status.create_info_field(),
+               Err(EncodeError::Conflict { field: "timestamp", other: "locator" }));
+
+    status.timestamp = None;
+    status.beam_heading = Some(90);
+    assert_eq!(status.create_info_field(), Err(EncodeError::Missing { field: "ERP" }));
+    status.erp = Some(20_000);
+    assert_eq!(status.create_info_field(), Err(EncodeError::OutOfRange { field: "ERP" }));
+    status.erp = Some(50);
+    assert_eq!(status.create_info_field().unwrap(), b">IO91/G^92".to_vec());
+}
@@ -0,0 +1,164 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::telemetry::{Telemetry, Definition, Equation, TelemetryContext, TelemetryStations,
+                      CompressedTelemetry, parse_telemetry, parse_definition};
+use aprs::structs::PositionFrame;
+use aprs::errors::{ParseError, EncodeError};
+
+mod common;
+use common::{assert_parse_errors, assert_round_trip};
+
+#[test]
+fn test_parse_telemetry() {
+    let frame = functions::parse_frame(b"N0QBF-11>APRS:T#005,199,000,255,073,123,01101001Solar").unwrap();
+    let telemetry = Telemetry::from_frame(&frame).unwrap();
+    assert_eq!(telemetry.sequence, "005");
+    assert_eq!(telemetry.analog, vec![199.0, 0.0, 255.0, 73.0, 123.0]);
+    assert_eq!(telemetry.digital, [false, true, true, false, true, false, false, true]);
+    assert_eq!(telemetry.comment, "Solar");
+}
+
+#[test]
+fn test_parse_telemetry_variants() {
+    let telemetry = parse_telemetry(b"T#MIC199,000,255,073,123,01101001").unwrap();
+    assert_eq!(telemetry.sequence, "MIC");
+    assert_eq!(telemetry.analog[0], 199.0);
+
+    let telemetry = parse_telemetry(b"T#MIC,1.5,-2,3").unwrap();
+    assert_eq!(telemetry.sequence, "MIC");
+    assert_eq!(telemetry.analog, vec![1.5, -2.0, 3.0]);
+    assert_eq!(telemetry.digital, [false; 8]);
+}
+
+#[test]
+fn test_parse_telemetry_errors() {
+    assert_parse_errors(parse_telemetry, &[
+        (b"T005,1", ParseError::InfoField { offset: 1 }),
+        (b"T#005", ParseError::InfoField { offset: 5 }),
+        (b"T#005,1,x,3", ParseError::InfoField { offset: 8 }),
+        (b"T#005,1,2,3,4,5,0110", ParseError::InfoField { offset: 20 }),
+        (b"T#005,1,2,3,4,5,01102001", ParseError::InfoField { offset: 20 }),
+    ]);
+}
+
+#[test]
+fn test_telemetry_round_trip() {
+    assert_round_trip(parse_telemetry, Telemetry::create_info_field,
+                      &[b"T#005,199,000,255,073,1.25,01101001Solar"]);
+
+    let mut telemetry = Telemetry::new();
+    telemetry.analog.pop();
+    assert_eq!(telemetry.create_info_field(), Err(EncodeError::TooShort { field: "analog", min: 5 }));
+    telemetry.analog.push(123_456_789.0);
+    assert_eq!(telemetry.create_info_field(), Err(EncodeError::OutOfRange { field: "analog" }));
+    telemetry.analog[4] = 1.0;
+    telemetry.sequence = "1000".to_string();
+    assert_eq!(telemetry.create_info_field(), Err(EncodeError::TooLong { field: "sequence", max: 3 }));
+}
+
+#[test]
+fn test_parse_definitions() {
+    assert_eq!(parse_definition("PARM.Battery,Temp,,Light,B1,Door").unwrap(), Definition::Parameters(
+        ["Battery", "Temp", "", "Light", "B1", "Door"].iter().map(|s| s.to_string()).collect()));
+    assert_eq!(parse_definition("UNIT.Volts,deg.F").unwrap(), Definition::Units(
+        vec!["Volts".to_string(), "deg.F".to_string()]));
+    assert_eq!(parse_definition("EQNS.0,0.075,0,0,10,0,0,1").unwrap(), Definition::Equations(vec![
+        Equation { a: 0.0, b: 0.075, c: 0.0 },
+        Equation { a: 0.0, b: 10.0, c: 0.0 },
+        Equation { a: 0.0, b: 1.0, c: 0.0 },
+    ]));
+    assert_eq!(parse_definition("BITS.10110000,Weather Station").unwrap(), Definition::Bits {
+        sense: [true, false, true, true, false, false, false, false],
+        project: "Weather Station".to_string(),
+    });
+    assert_eq!(parse_definition("EQNS.0,x,0"), Err(ParseError::InfoField { offset: 7 }));
+    assert_eq!(parse_definition("BITS.1011"), Err(ParseError::InfoField { offset: 9 }));
+    assert_eq!(parse_definition("Hello"), Err(ParseError::InfoField { offset: 0 }));
+}
+
+#[test]
+fn test_definition_to_message() {
+    let definition = Definition::Bits { sense: [true; 8], project: "Balloon".to_string() };
+    let message = definition.to_message("N0QBF-11");
+    assert_eq!(message.create_info_field().unwrap(), b":N0QBF-11 :BITS.11111111,Balloon".to_vec());
+    assert_eq!(Definition::from_message(&message).unwrap(), definition);
+}
+
+#[test]
+fn test_context_scale() {
+    let mut context = TelemetryContext::new();
+    context.apply(&parse_definition("PARM.Battery").unwrap());
+    context.apply(&parse_definition("UNIT.Volts,,,,,Open").unwrap());
+    context.apply(&parse_definition("EQNS.0.01,0.5,1").unwrap());
+    context.apply(&parse_definition("BITS.01111111,Test").unwrap());
+
+    let reading = context.scale(&parse_telemetry(b"T#001,10,20,3,4,5,00000000").unwrap());
+    assert_eq!(reading.analog[0].name, "Battery");
+    assert_eq!(reading.analog[0].unit, "Volts");
+    assert_eq!(reading.analog[0].value, 7.0);
+    assert_eq!(reading.analog[1].name, "A2");
+    assert_eq!(reading.analog[1].value, 20.0);
+    assert_eq!(reading.digital[0].name, "B1");
+    assert_eq!(reading.digital[0].label, "Open");
+    assert!(reading.digital[0].active);
+    assert!(!reading.digital[1].active);
+}
+
+#[test]
+fn test_stations() {
+    let mut stations = TelemetryStations::new();
+    assert_eq!(stations.receive(&functions::parse_frame(
+        b"N0QBF-11>APRS::N0QBF-11 :EQNS.0,2,0").unwrap()), None);
+    assert!(stations.context("n0qbf-11").is_some());
+
+    let reading = stations.receive(&functions::parse_frame(
+        b"N0QBF-11>APRS:T#005,100,0,0,0,0,00000000").unwrap()).unwrap();
+    assert_eq!(reading.analog[0].value, 200.0);
+    let reading = stations.receive(&functions::parse_frame(
+        b"KF4MKT>APRS:T#005,100,0,0,0,0,00000000").unwrap()).unwrap();
+    assert_eq!(reading.analog[0].value, 100.0);
+}
+
+#[test]
+fn test_compressed_telemetry() {
+    let telemetry = CompressedTelemetry::decode(b"!\"{{!!!#!$").unwrap();
+    assert_eq!(telemetry.sequence, 1);
+    assert_eq!(telemetry.analog, vec![8280, 0, 2, 3]);
+    assert_eq!(telemetry.digital, None);
+
+    let telemetry = CompressedTelemetry::decode(b"!\"!!!!!!!!!!!$").unwrap();
+    assert_eq!(telemetry.analog.len(), 5);
+    assert_eq!(telemetry.digital, Some([true, true, false, false, false, false, false, false]));
+    assert_eq!(telemetry.encode(), b"|!\"!!!!!!!!!!!$|".to_vec());
+    assert_eq!(telemetry.to_telemetry().sequence, "001");
+
+    assert_eq!(CompressedTelemetry::decode(b"!\""), None);
+    assert_eq!(CompressedTelemetry::decode(b"!\"!"), None);
+    assert_eq!(CompressedTelemetry::decode(b"!\"! "), None);
+}
+
+#[test]
+fn test_compressed_telemetry_encode_limits() {
+    let mut telemetry = CompressedTelemetry::new();
+    telemetry.sequence = 8281;
+    telemetry.analog = vec![9000];
+    assert_eq!(telemetry.encode(), b"|!!{{|".to_vec());
+    telemetry.digital = Some([false; 8]);
+    assert_eq!(telemetry.encode(), b"|!!{{!!!!!!!!!!|".to_vec());
+}
+
+#[test]
+fn test_position_telemetry() {
+    let frame = functions::parse_frame(b"N0QBF-11>APRS:!4903.50N/07201.75WOBalloon|!\"{{!!|up").unwrap();
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    assert_eq!(position.comment, b"Balloonup".to_vec());
+    let telemetry = position.telemetry.clone().unwrap();
+    assert_eq!(telemetry.analog, vec![8280, 0]);
+    assert!(position.create_info_field().ends_with(b"Balloonup|!\"{{!!|"));
+
+    // Pipes without a valid block are left in the comment
+    let frame = functions::parse_frame(b"N0QBF-11>APRS:!4903.50N/07201.75WOa|b|").unwrap();
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    assert_eq!(position.comment, b"a|b|".to_vec());
+    assert_eq!(position.telemetry, None);
+}
@@ -0,0 +1,96 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::payload::DataType;
+use aprs::structs::Frame;
+use aprs::status::Status;
+use aprs::third_party::{Header, ThirdParty, MAX_NESTING, parse_third_party};
+use aprs::errors::ParseError;
+
+mod common;
+use common::assert_parse_errors;
+
+#[test]
+fn test_parse_third_party() {
+    let frame = functions::parse_frame(
+        b"N0CALL-10>APRS,WIDE2-1:}W1AW-9>APDR15,TCPIP,N0CALL-10*:>Net Control Center").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::ThirdParty));
+    let third_party = ThirdParty::from_frame(&frame).unwrap();
+    assert_eq!(third_party.outer, Header::from_frame(&frame));
+    assert_eq!(third_party.inner.source.to_string(), "W1AW-9");
+    assert_eq!(third_party.inner.destination.to_string(), "APDR15");
+    assert_eq!(third_party.inner.path.len(), 2);
+    assert_eq!(third_party.inner.path[0].callsign, "TCPIP");
+    assert!(third_party.inner.path[1].digi);
+    assert_eq!(third_party.inner.info.data_type, Some(DataType::Status));
+    assert_eq!(third_party.nested, None);
+    assert_eq!(Status::from_frame(third_party.origin()).unwrap().text, "Net Control Center");
+}
+
+#[test]
+fn test_parse_nested_third_party() {
+    let frame = functions::parse_frame(
+        b"N0CALL>APRS:}N1CALL>APRS,TCPIP,N0CALL*:}W1AW>APRS,TCPIP,N1CALL*:!4903.50N/07201.75W-").unwrap();
+    let third_party = ThirdParty::from_frame(&frame).unwrap();
+    assert_eq!(third_party.inner.source.callsign, "N1CALL");
+    let nested = third_party.nested.clone().unwrap();
+    assert_eq!(nested.outer, Header::from_frame(&third_party.inner));
+    assert_eq!(nested.inner.source.callsign, "W1AW");
+    assert_eq!(third_party.origin(), &nested.inner);
+    assert_eq!(third_party.origin().info.data, b"!4903.50N/07201.75W-".to_vec());
+}
+
+#[test]
+fn test_parse_third_party_errors() {
+    assert_parse_errors(parse_third_party, &[
+        (b"}W1AW", ParseError::Address { offset: 5 }),
+        (b"}W1AW>APRS:", ParseError::InfoField { offset: 11 }),
+        (b">W1AW>APRS:>", ParseError::DataType { offset: 0, identifier: b'>' }),
+    ]);
+
+    // Offsets in nested traffic count from the outer information field
+    let frame = functions::parse_frame(b"N0CALL>APRS:}N1CALL>APRS:}W1AW>AP,,X:>").unwrap();
+    assert_eq!(ThirdParty::from_frame(&frame), Err(ParseError::Path { offset: 22 }));
+}
+
+#[test]
+fn test_nesting_limit() {
+    let nested = |levels: usize| -> Frame {
+        let mut raw: Vec<u8> = b"N0CALL>APRS:".to_vec();
+        for _ in 0..levels {
+            raw.extend(b"}A>B:");
+        }
+        raw.push(b'>');
+        functions::parse_frame(&raw).unwrap()
+    };
+    let third_party = ThirdParty::from_frame(&nested(MAX_NESTING)).unwrap();
+    assert_eq!(third_party.origin().info.data, b">".to_vec());
+    assert_eq!(ThirdParty::from_frame(&nested(MAX_NESTING + 1)),
+               Err(ParseError::InfoField { offset: 5 * MAX_NESTING }));
+    assert!(ThirdParty::from_frame(&nested(20_000)).is_err());
+}
+
+#[test]
+fn test_wrap() {
+    let frame = functions::parse_frame(b"W1AW-9>APDR15,qAC,T2TEXAS:>Net Control Center").unwrap();
+    let igate = functions::parse_callsign_text(b"N0CALL-10").unwrap();
+    let destination = functions::parse_callsign_text(b"APRS").unwrap();
+    let path = vec![functions::parse_callsign_text(b"WIDE2-1").unwrap()];
+    let third_party = ThirdParty::wrap(&frame, &igate, &destination, &path);
+
+    let expected: &[u8] = b"N0CALL-10>APRS,WIDE2-1:}W1AW-9>APDR15,TCPIP,N0CALL-10*:>Net Control Center";
+    assert_eq!(third_party.frame().encode_text(), expected.to_vec());
+    assert_eq!(third_party.frame().info.data_type, Some(DataType::ThirdParty));
+    assert_eq!(third_party.nested, None);
+    assert_eq!(ThirdParty::from_frame(&functions::parse_frame(expected).unwrap()).unwrap(), third_party);
+
+    // Wrapping third-party traffic again keeps every level
+    let twice = ThirdParty::wrap(&third_party.frame(), &igate, &destination, &Vec::new());
+    assert_eq!(twice.origin(), &third_party.inner);
+}
+
+#[test]
+fn test_encode_text() {
+    let raw: &[u8] = b"W2GMD-6>APRS,WIDE1-1*,WIDE2-1:!3745.75N/12228.05W#Inner Sunset";
+    let frame: Frame = functions::parse_frame(raw).unwrap();
+    assert_eq!(frame.encode_text(), raw.to_vec());
+}
@@ -0,0 +1,88 @@
+extern crate aprs;
+use std::time::Duration;
+
+use aprs::timestamp::Timestamp;
+use aprs::errors::ParseError;
+
+mod common;
+use common::assert_parse_errors;
+
+// 2024-03-01 00:10:00 UTC
+const MARCH_FIRST: u64 = 1_709_251_800;
+
+#[test]
+fn test_parse_format() {
+    let cases: [(&[u8], Timestamp); 4] = [
+        (b"092345z", Timestamp::DhmZulu { day: 9, hour: 23, minute: 45 }),
+        (b"092345/", Timestamp::DhmLocal { day: 9, hour: 23, minute: 45 }),
+        (b"234517h", Timestamp::Hms { hour: 23, minute: 45, second: 17 }),
+        (b"10090556", Timestamp::Mdhm { month: 10, day: 9, hour: 5, minute: 56 }),
+    ];
+    for &(raw, timestamp) in cases.iter() {
+        assert_eq!(Timestamp::parse(raw), Ok(timestamp));
+        assert_eq!(timestamp.to_string().as_bytes(), raw);
+    }
+    assert_eq!(Timestamp::parse(b"000000z"), Ok(Timestamp::DhmZulu { day: 0, hour: 0, minute: 0 }));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_parse_errors(Timestamp::parse, &[
+        (b"0923", ParseError::InfoField { offset: 4 }),
+        (b"09x345z", ParseError::InfoField { offset: 2 }),
+        (b"092345x", ParseError::InfoField { offset: 6 }),
+        (b"1009055", ParseError::InfoField { offset: 7 }),
+        (b"322345z", ParseError::InfoField { offset: 0 }),
+        (b"092360z", ParseError::InfoField { offset: 4 }),
+        (b"236017h", ParseError::InfoField { offset: 2 }),
+        (b"13090556", ParseError::InfoField { offset: 0 }),
+        (b"00090556", ParseError::InfoField { offset: 0 }),
+    ]);
+}
+
+#[test]
+fn test_from_instant() {
+    let now = Duration::from_secs(MARCH_FIRST);
+    assert_eq!(Timestamp::dhm_zulu(now), Timestamp::DhmZulu { day: 1, hour: 0, minute: 10 });
+    assert_eq!(Timestamp::hms(now), Timestamp::Hms { hour: 0, minute: 10, second: 0 });
+    assert_eq!(Timestamp::mdhm(now), Timestamp::Mdhm { month: 3, day: 1, hour: 0, minute: 10 });
+}
+
+#[test]
+fn test_resolve_month_rollover() {
+    let now = Duration::from_secs(MARCH_FIRST);
+    // 2024-02-29 23:58, the previous month of a leap year
+    let timestamp = Timestamp::DhmZulu { day: 29, hour: 23, minute: 58 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(MARCH_FIRST - 12 * 60)));
+    // Same day and month
+    let timestamp = Timestamp::DhmZulu { day: 1, hour: 0, minute: 5 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(MARCH_FIRST - 5 * 60)));
+    // Day 30 exists in March but not February
+    let timestamp = Timestamp::DhmZulu { day: 30, hour: 0, minute: 0 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(MARCH_FIRST - 10 * 60 + 29 * 86_400)));
+    assert_eq!(Timestamp::DhmZulu { day: 0, hour: 0, minute: 0 }.resolve(now), None);
+}
+
+#[test]
+fn test_resolve_year_rollover() {
+    // 2023-12-31 23:55 UTC
+    let now = Duration::from_secs(1_704_066_900);
+    // 2024-01-01 00:02, slightly ahead of a skewed clock
+    let timestamp = Timestamp::DhmZulu { day: 1, hour: 0, minute: 2 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(1_704_067_320)));
+
+    // 2024-01-01 00:05 UTC
+    let now = Duration::from_secs(1_704_067_500);
+    let timestamp = Timestamp::Mdhm { month: 12, day: 31, hour: 23, minute: 50 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(1_704_066_600)));
+}
+
+#[test]
+fn test_resolve_hms_and_local() {
+    let now = Duration::from_secs(MARCH_FIRST);
+    let timestamp = Timestamp::Hms { hour: 23, minute: 59, second: 30 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(MARCH_FIRST - 10 * 60 - 30)));
+    let timestamp = Timestamp::Hms { hour: 0, minute: 9, second: 0 };
+    assert_eq!(timestamp.resolve(now), Some(Duration::from_secs(MARCH_FIRST - 60)));
+    assert_eq!(Timestamp::DhmLocal { day: 1, hour: 0, minute: 0 }.resolve(now), None);
+}
@@ -0,0 +1,177 @@
+extern crate aprs;
+use aprs::functions;
+use aprs::payload::DataType;
+use aprs::structs::PositionFrame;
+use aprs::timestamp::Timestamp;
+use aprs::weather::{Weather, parse_weather, parse_peet_bros, parse_ultimeter};
+use aprs::errors::{ParseError, EncodeError};
+
+mod common;
+use common::{assert_parse_errors, assert_round_trip};
+
+#[test]
+fn test_parse_positionless() {
+    let frame = functions::parse_frame(b"N0CALL>APRS:_10090556c220s004g005t077r001p002P003h50b09900wRSW").unwrap();
+    let weather = Weather::from_frame(&frame).unwrap();
+    assert_eq!(weather.timestamp, Some(Timestamp::Mdhm { month: 10, day: 9, hour: 5, minute: 56 }));
+    assert_eq!(weather.wind_direction, Some(220));
+    assert_eq!(weather.wind_speed, Some(4));
+    assert_eq!(weather.wind_gust, Some(5));
+    assert_eq!(weather.temperature, Some(77));
+    assert_eq!(weather.rain_1h, Some(1));
+    assert_eq!(weather.rain_24h, Some(2));
+    assert_eq!(weather.rain_midnight, Some(3));
+    assert_eq!(weather.humidity, Some(50));
+    assert_eq!(weather.pressure, Some(9900));
+    assert_eq!(weather.comment, b"wRSW".to_vec());
+}
+
+#[test]
+fn test_parse_fields() {
+    let weather = parse_weather(b"_10090556c...s...g...t-05h00L123s1.5#010").unwrap();
+    assert_eq!(weather.wind_direction, None);
+    assert_eq!(weather.wind_gust, None);
+    assert_eq!(weather.temperature, Some(-5));
+    assert_eq!(weather.humidity, Some(100));
+    assert_eq!(weather.luminosity, Some(123));
+    assert_eq!(weather.snow, Some(1.5));
+    assert_eq!(weather.rain_raw, Some(10));
+    assert!(weather.comment.is_empty());
+
+    assert_eq!(parse_weather(b"_10090556l012").unwrap().luminosity, Some(1012));
+    assert_eq!(parse_weather(b"_10090556t7x5").unwrap().comment, b"t7x5".to_vec());
+    assert_parse_errors(parse_weather, &[
+        (b"_1009055", ParseError::InfoField { offset: 8 }),
+        (b"_1009x556", ParseError::InfoField { offset: 5 }),
+    ]);
+}
+
+#[test]
+fn test_accessors() {
+    let weather = parse_weather(b"_10090556c220s010g020t050r100b10132s010").unwrap();
+    assert!((weather.wind_speed_ms().unwrap() - 4.4704).abs() < 1e-4);
+    assert!((weather.wind_gust_ms().unwrap() - 8.9408).abs() < 1e-4);
+    assert!((weather.temperature_celsius().unwrap() - 10.0).abs() < 1e-4);
+    assert!((weather.rain_1h_mm().unwrap() - 25.4).abs() < 1e-4);
+    assert!((weather.pressure_hpa().unwrap() - 1013.2).abs() < 1e-3);
+    assert!((weather.snow_cm().unwrap() - 25.4).abs() < 1e-4);
+    assert_eq!(weather.rain_24h_mm(), None);
+}
+
+#[test]
+fn test_positionless_round_trip() {
+    assert_round_trip(parse_weather, Weather::create_info_field,
+                      &[b"_10090556c220s004g005t-05r001p002P003h50b09900l012s1.5wRSW"]);
+
+    let mut weather = Weather::new();
+    assert_eq!(weather.create_info_field(), Err(EncodeError::Missing { field: "timestamp" }));
+    weather.timestamp = Some(Timestamp::Mdhm { month: 10, day: 9, hour: 5, minute: 56 });
+    weather.temperature = Some(1500);
+    weather.humidity = Some(100);
+    assert_eq!(weather.create_info_field().unwrap(), b"_10090556c...s...g...t999h00".to_vec());
+}
+
+#[test]
+fn test_position_weather() {
+    let frame = functions::parse_frame(
+        b"N0CALL>APRS:@092345z4903.50N/07201.75W_220/004g005t077r000p000P000h50b09900wRSW").unwrap();
+    let position = PositionFrame::from_frame(&frame).unwrap();
+    let weather = position.weather.clone().unwrap();
+    assert_eq!(weather.wind_direction, Some(220));
+    assert_eq!(weather.wind_speed, Some(4));
+    assert_eq!(weather.temperature, Some(77));
+    assert_eq!(position.comment, b"wRSW".to_vec());
+    assert_eq!(position.create_info_field(), frame.info.data);
+    assert_eq!(Weather::from_frame(&frame).unwrap(), weather);
+
+    // Other symbols keep their course and speed in the comment
+    let frame = functions::parse_frame(b"N0CALL>APRS:!4903.50N/07201.75W>220/004g005").unwrap();
+    assert_eq!(PositionFrame::from_frame(&frame).unwrap().weather, None);
+    assert!(Weather::from_frame(&frame).is_err());
+}
+
+#[test]
+fn test_compressed_position_weather() {
+    let mut position = PositionFrame::new();
+    position.compressed = true;
+    position.table = b"/".to_vec();
+    position.symbol = b'_';
+    position.lat = 49.5;
+    position.lng = -72.75;
+    let mut weather = Weather::new();
+    weather.wind_direction = Some(88);
+    weather.wind_speed = Some(21);
+    weather.temperature = Some(60);
+    position.weather = Some(weather);
+
+    let parsed = functions::parse_position(&position.create_info_field()).unwrap();
+    assert_eq!(parsed.course, None);
+    let weather = parsed.weather.unwrap();
+    assert_eq!(weather.wind_direction, Some(88));
+    // The compressed speed is logarithmic, so only approximately recovered
+    assert!((i32::from(weather.wind_speed.unwrap()) - 21).abs() <= 1);
+    assert_eq!(weather.wind_gust, None);
+    assert_eq!(weather.temperature, Some(60));
+}
+
+#[test]
+fn test_parse_peet_bros() {
+    let frame = functions::parse_frame(b"N0CALL>APRS:#W140006402CE0069").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::PeetBros));
+    let weather = Weather::from_frame(&frame).unwrap();
+    assert_eq!(weather.wind_direction, Some(90));
+    assert_eq!(weather.wind_speed, Some(10));
+    assert_eq!(weather.temperature, Some(72));
+    assert_eq!(weather.rain_raw, Some(105));
+
+    let weather = parse_peet_bros(b"*400064FFF6").unwrap();
+    assert_eq!(weather.wind_speed, Some(6));
+    assert_eq!(weather.temperature, Some(-1));
+    assert_eq!(weather.rain_raw, None);
+
+    assert_parse_errors(parse_peet_bros, &[
+        (b"#W2400064", ParseError::InfoField { offset: 1 }),
+        (b"*4000x4", ParseError::InfoField { offset: 5 }),
+    ]);
+}
+
+#[test]
+fn test_parse_ultimeter() {
+    let frame = functions::parse_frame(
+        b"N0CALL>APRS:$ULTW0031003702CE0069----000086A00001----011901CC00000005").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::Ultimeter));
+    let weather = Weather::from_frame(&frame).unwrap();
+    assert_eq!(weather.wind_gust, Some(3));
+    assert_eq!(weather.wind_direction, Some(77));
+    assert_eq!(weather.temperature, Some(72));
+    assert_eq!(weather.rain_raw, Some(105));
+    assert_eq!(weather.pressure, None);
+    assert_eq!(weather.humidity, None);
+    assert_eq!(weather.rain_midnight, Some(0));
+    assert_eq!(weather.wind_speed, Some(0));
+
+    let weather = parse_ultimeter(b"$ULTW0000000000000000271000000000000003E80000000000000000\r\n").unwrap();
+    assert_eq!(weather.pressure, Some(10000));
+    assert_eq!(weather.humidity, Some(100));
+    assert_eq!(weather.comment, b"\r\n".to_vec());
+
+    // Logging mode
+    let frame = functions::parse_frame(
+        b"N0CALL>APRS:!!00000066013D000028710166--------0158053201200210").unwrap();
+    assert_eq!(frame.info.data_type, Some(DataType::Ultimeter));
+    let weather = Weather::from_frame(&frame).unwrap();
+    assert_eq!(weather.wind_speed, Some(33));
+    assert_eq!(weather.wind_direction, Some(143));
+    assert_eq!(weather.temperature, Some(32));
+    assert_eq!(weather.rain_raw, Some(0));
+    assert_eq!(weather.pressure, Some(10353));
+    assert_eq!(weather.humidity, None);
+    assert_eq!(weather.rain_midnight, Some(288));
+
+    assert_parse_errors(parse_ultimeter, &[
+        (b"$ULTW00310037", ParseError::InfoField { offset: 13 }),
+        (b"!!0000006601", ParseError::InfoField { offset: 12 }),
+        (b"$GPRMC,", ParseError::InfoField { offset: 1 }),
+    ]);
+    assert_eq!(functions::parse_info_field(b"$GPRMC,").unwrap().data_type, Some(DataType::Nmea));
+}